@@ -0,0 +1,473 @@
+use super::BroadPhase;
+use crate::geometry::aabb::Aabb;
+use crate::math::math_essentials::*;
+
+use std::collections::HashMap;
+
+/**
+ * Margin added on every side of the AABB of an object when it is inserted in the tree,
+ * as long as the object stays inside its fattened box, it isn't reinserted.
+ */
+pub const AABB_MARGIN: Real = 0.1;
+
+#[derive(Clone, Debug)]
+struct Node {
+    aabb: Aabb,
+    parent: Option<usize>,
+    // both None for a leaf, both Some for an internal node
+    child1: Option<usize>,
+    child2: Option<usize>,
+    // id of the object for a leaf
+    id: usize,
+    // leaf = 0
+    height: i32,
+}
+
+impl Node {
+    fn is_leaf(&self) -> bool {
+        self.child1.is_none()
+    }
+}
+
+/**
+ * Dynamic bounding volume hierarchy, the leaves are the fattened AABBs of the objects.
+ *
+ * Inspired by the b2DynamicTree of Box2D :
+ * - the insertion follows the sibling which minimise the increase of surface area,
+ * - the tree is kept balanced with rotations on the way up.
+ *
+ * The objects with infinite bounds (Plane) are kept out of the tree, in `unbounded`,
+ * they overlap every other object.
+ */
+pub struct DynamicAabbTree {
+    nodes: Vec<Node>,
+    free_nodes: Vec<usize>,
+    root: Option<usize>,
+    // id of the object -> index of its leaf
+    leaves: HashMap<usize, usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Default for DynamicAabbTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicAabbTree {
+    pub fn new() -> DynamicAabbTree {
+        DynamicAabbTree {
+            nodes: Vec::new(),
+            free_nodes: Vec::new(),
+            root: None,
+            leaves: HashMap::new(),
+            unbounded: Vec::new(),
+        }
+    }
+
+    /**
+     * Fattened AABB stored for this object, None if unknown or unbounded.
+     */
+    pub fn fat_aabb(&self, id: usize) -> Option<&Aabb> {
+        self.leaves.get(&id).map(|leaf| &self.nodes[*leaf].aabb)
+    }
+
+    pub fn height(&self) -> i32 {
+        match self.root {
+            Some(root) => self.nodes[root].height,
+            None => 0,
+        }
+    }
+
+    fn allocate_node(&mut self, node: Node) -> usize {
+        match self.free_nodes.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let root = match self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(leaf);
+                self.nodes[leaf].parent = None;
+                return;
+            }
+        };
+
+        // find the best sibling
+        let leaf_aabb = self.nodes[leaf].aabb;
+        let mut index = root;
+        while !self.nodes[index].is_leaf() {
+            let child1 = self.nodes[index].child1.unwrap();
+            let child2 = self.nodes[index].child2.unwrap();
+
+            let area = self.nodes[index].aabb.surface_area();
+            let combined_area = self.nodes[index].aabb.merged(&leaf_aabb).surface_area();
+
+            // cost of creating a new parent for this node and the new leaf
+            let cost = TWO * combined_area;
+            // minimum cost of pushing the leaf further down the tree
+            let inheritance_cost = TWO * (combined_area - area);
+
+            let cost1 = self.descend_cost(child1, &leaf_aabb) + inheritance_cost;
+            let cost2 = self.descend_cost(child2, &leaf_aabb) + inheritance_cost;
+
+            if cost < cost1 && cost < cost2 {
+                break;
+            }
+
+            index = if cost1 < cost2 { child1 } else { child2 };
+        }
+        let sibling = index;
+
+        // create a new parent
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node(Node {
+            aabb: leaf_aabb.merged(&self.nodes[sibling].aabb),
+            parent: old_parent,
+            child1: Some(sibling),
+            child2: Some(leaf),
+            id: usize::MAX,
+            height: self.nodes[sibling].height + 1,
+        });
+        self.nodes[sibling].parent = Some(new_parent);
+        self.nodes[leaf].parent = Some(new_parent);
+
+        match old_parent {
+            Some(old_parent) => {
+                if self.nodes[old_parent].child1 == Some(sibling) {
+                    self.nodes[old_parent].child1 = Some(new_parent);
+                } else {
+                    self.nodes[old_parent].child2 = Some(new_parent);
+                }
+            }
+            None => self.root = Some(new_parent),
+        }
+
+        // walk back up the tree fixing heights and AABBs
+        self.refit_from(self.nodes[leaf].parent);
+    }
+
+    fn descend_cost(&self, child: usize, leaf_aabb: &Aabb) -> Real {
+        let node = &self.nodes[child];
+        let merged_area = leaf_aabb.merged(&node.aabb).surface_area();
+        if node.is_leaf() {
+            merged_area
+        } else {
+            merged_area - node.aabb.surface_area()
+        }
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if self.root == Some(leaf) {
+            self.root = None;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent.unwrap();
+        let grand_parent = self.nodes[parent].parent;
+        let sibling = if self.nodes[parent].child1 == Some(leaf) {
+            self.nodes[parent].child2.unwrap()
+        } else {
+            self.nodes[parent].child1.unwrap()
+        };
+
+        match grand_parent {
+            Some(grand_parent) => {
+                // destroy parent and connect sibling to grand_parent
+                if self.nodes[grand_parent].child1 == Some(parent) {
+                    self.nodes[grand_parent].child1 = Some(sibling);
+                } else {
+                    self.nodes[grand_parent].child2 = Some(sibling);
+                }
+                self.nodes[sibling].parent = Some(grand_parent);
+                self.free_nodes.push(parent);
+
+                self.refit_from(Some(grand_parent));
+            }
+            None => {
+                self.root = Some(sibling);
+                self.nodes[sibling].parent = None;
+                self.free_nodes.push(parent);
+            }
+        }
+    }
+
+    fn refit_from(&mut self, mut index: Option<usize>) {
+        while let Some(i) = index {
+            let i = self.balance(i);
+
+            let child1 = self.nodes[i].child1.unwrap();
+            let child2 = self.nodes[i].child2.unwrap();
+            self.nodes[i].height =
+                1 + i32::max(self.nodes[child1].height, self.nodes[child2].height);
+            self.nodes[i].aabb = self.nodes[child1].aabb.merged(&self.nodes[child2].aabb);
+
+            index = self.nodes[i].parent;
+        }
+    }
+
+    /**
+     * Perform a left or right rotation if the node `a` is imbalanced.
+     * Returns the new root index of the subtree.
+     */
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let b = self.nodes[a].child1.unwrap();
+        let c = self.nodes[a].child2.unwrap();
+        let balance = self.nodes[c].height - self.nodes[b].height;
+
+        if balance > 1 {
+            self.rotate_up(a, c, b)
+        } else if balance < -1 {
+            self.rotate_up(a, b, c)
+        } else {
+            a
+        }
+    }
+
+    /**
+     * `high` is the child of `a` that is too high, `low` the other one.
+     * `high` takes the place of `a`, and `a` takes the lowest child of `high`.
+     */
+    fn rotate_up(&mut self, a: usize, high: usize, low: usize) -> usize {
+        let f = self.nodes[high].child1.unwrap();
+        let g = self.nodes[high].child2.unwrap();
+
+        // swap a and high
+        self.nodes[high].child1 = Some(a);
+        self.nodes[high].parent = self.nodes[a].parent;
+        self.nodes[a].parent = Some(high);
+
+        match self.nodes[high].parent {
+            Some(p) => {
+                if self.nodes[p].child1 == Some(a) {
+                    self.nodes[p].child1 = Some(high);
+                } else {
+                    self.nodes[p].child2 = Some(high);
+                }
+            }
+            None => self.root = Some(high),
+        }
+
+        // the highest grand child stays under high, the other one goes under a
+        let (kept, moved) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+        self.nodes[high].child2 = Some(kept);
+        if self.nodes[a].child1 == Some(high) {
+            self.nodes[a].child1 = Some(moved);
+        } else {
+            self.nodes[a].child2 = Some(moved);
+        }
+        self.nodes[moved].parent = Some(a);
+        debug_assert!(self.nodes[a].child1 == Some(low) || self.nodes[a].child2 == Some(low));
+
+        self.nodes[a].aabb = self.nodes[low].aabb.merged(&self.nodes[moved].aabb);
+        self.nodes[a].height = 1 + i32::max(self.nodes[low].height, self.nodes[moved].height);
+        self.nodes[high].aabb = self.nodes[a].aabb.merged(&self.nodes[kept].aabb);
+        self.nodes[high].height = 1 + i32::max(self.nodes[a].height, self.nodes[kept].height);
+
+        high
+    }
+
+    fn query_tree(&self, aabb: &Aabb, mut callback: impl FnMut(usize)) {
+        let mut stack = Vec::<usize>::new();
+        if let Some(root) = self.root {
+            stack.push(root);
+        }
+
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+            if node.is_leaf() {
+                callback(node.id);
+            } else {
+                stack.push(node.child1.unwrap());
+                stack.push(node.child2.unwrap());
+            }
+        }
+    }
+}
+
+impl BroadPhase for DynamicAabbTree {
+    fn insert(&mut self, id: usize, aabb: Aabb) {
+        debug_assert!(!self.leaves.contains_key(&id) && !self.unbounded.contains(&id));
+        if !aabb.is_finite() {
+            self.unbounded.push(id);
+            return;
+        }
+
+        let leaf = self.allocate_node(Node {
+            aabb: aabb.fattened(AABB_MARGIN),
+            parent: None,
+            child1: None,
+            child2: None,
+            id,
+            height: 0,
+        });
+        self.leaves.insert(id, leaf);
+        self.insert_leaf(leaf);
+    }
+
+    /**
+     * The object is only reinserted if it went out of its fattened AABB.
+     */
+    fn update(&mut self, id: usize, aabb: Aabb) {
+        if let Some(leaf) = self.leaves.get(&id) {
+            if self.nodes[*leaf].aabb.contains(&aabb) {
+                return;
+            }
+        } else if !self.unbounded.contains(&id) {
+            panic!("DynamicAabbTree::update(id), the id isn't in the broadphase");
+        }
+
+        self.remove(id);
+        self.insert(id, aabb);
+    }
+
+    fn remove(&mut self, id: usize) {
+        if let Some(leaf) = self.leaves.remove(&id) {
+            self.remove_leaf(leaf);
+            self.free_nodes.push(leaf);
+        } else if let Some(i) = self.unbounded.iter().position(|x| *x == id) {
+            self.unbounded.swap_remove(i);
+        }
+    }
+
+    fn compute_pairs(&mut self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+
+        for (id, leaf) in &self.leaves {
+            self.query_tree(&self.nodes[*leaf].aabb, |other| {
+                // each pair is found twice, keep only one
+                if *id < other {
+                    pairs.push((*id, other));
+                }
+            });
+        }
+
+        for (i, id) in self.unbounded.iter().enumerate() {
+            for other in self.leaves.keys().chain(self.unbounded.iter().skip(i + 1)) {
+                pairs.push((usize::min(*id, *other), usize::max(*id, *other)));
+            }
+        }
+
+        pairs
+    }
+
+    fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        self.query_tree(aabb, |id| result.push(id));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(center: P3) -> Aabb {
+        Aabb::from_center_half_extents(center, Vec3::value(0.5))
+    }
+
+    #[test]
+    fn tree_pairs_and_query() {
+        let mut tree = DynamicAabbTree::new();
+        for i in 0..100 {
+            tree.insert(i, unit_box(P3::new(2.0 * i as Real, ZERO, ZERO)));
+        }
+        // balanced
+        assert!(tree.height() < 15);
+        assert_eq!(tree.compute_pairs().len(), 0);
+
+        tree.update(3, unit_box(P3::new(5.0, ZERO, ZERO)));
+        let mut pairs = tree.compute_pairs();
+        pairs.sort();
+        assert_eq!(pairs, vec![(2, 3)]);
+
+        let mut r = tree.query_aabb(&Aabb::new(
+            P3::new(-1.0, -1.0, -1.0),
+            P3::new(2.2, 1.0, 1.0),
+        ));
+        r.sort();
+        assert_eq!(r, vec![0, 1]);
+
+        tree.insert(1000, Aabb::infinite());
+        assert_eq!(tree.compute_pairs().len(), 101);
+        assert_eq!(
+            tree.query_aabb(&unit_box(P3::new(ZERO, 50.0, ZERO))),
+            vec![1000]
+        );
+
+        tree.remove(1000);
+        tree.remove(3);
+        assert_eq!(tree.compute_pairs().len(), 0);
+    }
+
+    #[test]
+    fn tree_small_moves_dont_reinsert() {
+        let mut tree = DynamicAabbTree::new();
+        tree.insert(0, unit_box(P3::origin()));
+        tree.insert(1, unit_box(P3::new(5.0, ZERO, ZERO)));
+        let fat = *tree.fat_aabb(0).unwrap();
+
+        tree.update(0, unit_box(P3::new(AABB_MARGIN * 0.5, ZERO, ZERO)));
+        assert_eq!(tree.fat_aabb(0).unwrap().min, fat.min);
+
+        tree.update(0, unit_box(P3::new(ONE, ZERO, ZERO)));
+        assert_ne!(tree.fat_aabb(0).unwrap().min, fat.min);
+    }
+
+    #[test]
+    fn tree_same_pairs_as_brute_force() {
+        // small deterministic pseudo random generator
+        let mut seed: u32 = 12345;
+        let mut random = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as Real / 65536.0
+        };
+
+        let mut tree = DynamicAabbTree::new();
+        let mut boxes = Vec::new();
+        for i in 0..200 {
+            let b = unit_box(P3::new(random() * 30.0, random() * 30.0, random() * 30.0));
+            tree.insert(i, b);
+            boxes.push(b);
+        }
+
+        for _ in 0..5 {
+            for (i, b) in boxes.iter_mut().enumerate() {
+                let c = (b.min + b.max) * 0.5
+                    + Vec3::new(random() - 0.5, random() - 0.5, random() - 0.5);
+                *b = unit_box(c);
+                tree.update(i, *b);
+            }
+
+            // the tree works on fattened boxes, so it finds every real pair
+            let pairs = tree.compute_pairs();
+            for i in 0..boxes.len() {
+                for j in i + 1..boxes.len() {
+                    if boxes[i].intersects(&boxes[j]) {
+                        assert!(pairs.contains(&(i, j)));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+pub mod dynamic_aabb_tree;
+pub mod sweep_and_prune;
+
+pub use dynamic_aabb_tree::DynamicAabbTree;
+pub use sweep_and_prune::SweepAndPrune;
+
+use crate::geometry::aabb::Aabb;
+
+/**
+ * Broadphase used by the CollisionWorld, the choice depends on the scene :
+ * - SweepAndPrune for many small moving objects,
+ * - DynamicAabbTree for big static levels, it is also faster for world queries.
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BroadPhaseType {
+    SweepAndPrune,
+    DynamicAabbTree,
+}
+
+impl BroadPhaseType {
+    pub fn create(&self) -> Box<dyn BroadPhase> {
+        match self {
+            BroadPhaseType::SweepAndPrune => Box::new(SweepAndPrune::new()),
+            BroadPhaseType::DynamicAabbTree => Box::new(DynamicAabbTree::new()),
+        }
+    }
+}
+
+/**
+ * Keep track of the AABB of each CollisionObject (by id)
+ * to find the pairs that need to go through the narrowphase.
+ */
+pub trait BroadPhase {
+    fn insert(&mut self, id: usize, aabb: Aabb);
+    fn update(&mut self, id: usize, aabb: Aabb);
+    fn remove(&mut self, id: usize);
+    /**
+     * Every pair of ids that may overlap, as (smallest id, biggest id), each pair only once.
+     */
+    fn compute_pairs(&mut self) -> Vec<(usize, usize)>;
+    /**
+     * Ids whose bounds in the broadphase overlap `aabb`, it may contains false positives.
+     */
+    fn query_aabb(&self, aabb: &Aabb) -> Vec<usize>;
+}
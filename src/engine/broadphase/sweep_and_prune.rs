@@ -0,0 +1,229 @@
+use super::BroadPhase;
+use crate::geometry::aabb::Aabb;
+use crate::math::math_essentials::*;
+
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, Debug)]
+struct Endpoint {
+    value: Real,
+    id: usize,
+    is_min: bool,
+}
+
+impl Endpoint {
+    /**
+     * At equal values, a min endpoint comes before a max endpoint
+     * so that touching boxes are reported as overlapping.
+     */
+    fn is_before(&self, o: &Endpoint) -> bool {
+        self.value < o.value || (self.value == o.value && self.is_min && !o.is_min)
+    }
+}
+
+/**
+ * Sweep and prune (sort and sweep) broadphase.
+ *
+ * The endpoints of the AABBs are kept sorted on each axis between steps,
+ * since the objects move a little from one step to another, the lists are almost sorted
+ * and the insertion sort runs in nearly O(n).
+ * The sweep is done on the axis where the objects are the most spread out.
+ */
+pub struct SweepAndPrune {
+    boxes: HashMap<usize, Aabb>,
+    endpoints: [Vec<Endpoint>; 3],
+}
+
+impl Default for SweepAndPrune {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SweepAndPrune {
+    pub fn new() -> SweepAndPrune {
+        SweepAndPrune {
+            boxes: HashMap::new(),
+            endpoints: [Vec::new(), Vec::new(), Vec::new()],
+        }
+    }
+
+    fn update_and_sort_axis(&mut self, axis: usize) {
+        let endpoints = &mut self.endpoints[axis];
+        for e in endpoints.iter_mut() {
+            let aabb = &self.boxes[&e.id];
+            e.value = if e.is_min {
+                aabb.min[axis]
+            } else {
+                aabb.max[axis]
+            };
+        }
+
+        // insertion sort, cheap on the nearly sorted list from the previous step
+        for i in 1..endpoints.len() {
+            let e = endpoints[i];
+            let mut j = i;
+            while j > 0 && e.is_before(&endpoints[j - 1]) {
+                endpoints[j] = endpoints[j - 1];
+                j -= 1;
+            }
+            endpoints[j] = e;
+        }
+    }
+
+    /**
+     * The axis with the biggest variance of the (finite) centers of the boxes.
+     */
+    fn sweep_axis(&self) -> usize {
+        let mut sum = Vec3::zeros();
+        let mut sum_squared = Vec3::zeros();
+        let mut n = 0;
+
+        for aabb in self.boxes.values() {
+            let center = (aabb.min + aabb.max) * 0.5;
+            if !center[0].is_finite() || !center[1].is_finite() || !center[2].is_finite() {
+                continue;
+            }
+            sum += center;
+            sum_squared += center * center;
+            n += 1;
+        }
+
+        if n == 0 {
+            return 0;
+        }
+
+        let n = n as Real;
+        let mut best_axis = 0;
+        let mut best_variance = Real::NEG_INFINITY;
+        for axis in 0..3 {
+            let mean = sum[axis] / n;
+            let variance = sum_squared[axis] / n - mean * mean;
+            if variance > best_variance {
+                best_variance = variance;
+                best_axis = axis;
+            }
+        }
+        best_axis
+    }
+}
+
+impl BroadPhase for SweepAndPrune {
+    fn insert(&mut self, id: usize, aabb: Aabb) {
+        debug_assert!(!self.boxes.contains_key(&id));
+        self.boxes.insert(id, aabb);
+
+        for (axis, endpoints) in self.endpoints.iter_mut().enumerate() {
+            endpoints.push(Endpoint {
+                value: aabb.min[axis],
+                id,
+                is_min: true,
+            });
+            endpoints.push(Endpoint {
+                value: aabb.max[axis],
+                id,
+                is_min: false,
+            });
+        }
+    }
+
+    fn update(&mut self, id: usize, aabb: Aabb) {
+        match self.boxes.get_mut(&id) {
+            Some(x) => *x = aabb,
+            None => panic!("SweepAndPrune::update(id), the id isn't in the broadphase"),
+        }
+    }
+
+    fn remove(&mut self, id: usize) {
+        if self.boxes.remove(&id).is_some() {
+            for endpoints in self.endpoints.iter_mut() {
+                endpoints.retain(|e| e.id != id);
+            }
+        }
+    }
+
+    fn compute_pairs(&mut self) -> Vec<(usize, usize)> {
+        for axis in 0..3 {
+            self.update_and_sort_axis(axis);
+        }
+
+        let axis = self.sweep_axis();
+        let mut pairs = Vec::new();
+        let mut active = Vec::<usize>::new();
+
+        for e in &self.endpoints[axis] {
+            if e.is_min {
+                let aabb = &self.boxes[&e.id];
+                for other in &active {
+                    if aabb.intersects(&self.boxes[other]) {
+                        pairs.push((usize::min(e.id, *other), usize::max(e.id, *other)));
+                    }
+                }
+                active.push(e.id);
+            } else if let Some(i) = active.iter().position(|x| *x == e.id) {
+                active.swap_remove(i);
+            }
+        }
+
+        pairs
+    }
+
+    fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        self.boxes
+            .iter()
+            .filter(|(_, x)| x.intersects(aabb))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_and_prune_pairs() {
+        let mut sap = SweepAndPrune::new();
+        sap.insert(
+            0,
+            Aabb::from_center_half_extents(P3::origin(), Vec3::ones()),
+        );
+        sap.insert(
+            1,
+            Aabb::from_center_half_extents(P3::new(1.5, ZERO, ZERO), Vec3::ones()),
+        );
+        sap.insert(
+            2,
+            Aabb::from_center_half_extents(P3::new(1.5, 5.0, ZERO), Vec3::ones()),
+        );
+        sap.insert(3, Aabb::infinite());
+
+        let mut pairs = sap.compute_pairs();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 1), (0, 3), (1, 3), (2, 3)]);
+
+        // 2 moves down onto 1, 0 moves away
+        sap.update(
+            2,
+            Aabb::from_center_half_extents(P3::new(1.5, 1.5, ZERO), Vec3::ones()),
+        );
+        sap.update(
+            0,
+            Aabb::from_center_half_extents(P3::new(-5.0, ZERO, ZERO), Vec3::ones()),
+        );
+
+        let mut pairs = sap.compute_pairs();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 3), (1, 2), (1, 3), (2, 3)]);
+
+        sap.remove(3);
+        assert_eq!(sap.compute_pairs(), vec![(1, 2)]);
+        assert_eq!(
+            sap.query_aabb(&Aabb::from_center_half_extents(
+                P3::new(-5.0, ZERO, ZERO),
+                Vec3::ones()
+            )),
+            vec![0]
+        );
+    }
+}
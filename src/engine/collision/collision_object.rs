@@ -1,12 +1,33 @@
+use super::Material;
 use crate::engine::shapes::Shape;
 use crate::math::math_essentials::*;
 
+/**
+ * Mask with every collision group
+ */
+pub const ALL_GROUPS: u32 = u32::MAX;
+
+/**
+ * User callback deciding if two CollisionObjects can collide,
+ * called only if their collision groups already allow it.
+ */
+pub type PairFilter = fn(&CollisionObject, &CollisionObject) -> bool;
+
 pub struct CollisionObject {
     pub enabled: bool,
     pub id: usize,
     pub shape: Box<dyn Shape>,
     pub rigidbody_id: Option<usize>,
     pub is_static: bool,
+    pub is_kinematic: bool,
+    // groups (bits) the object belongs to
+    pub membership: u32,
+    // groups (bits) the object can collide with
+    pub filter: u32,
+    pub pair_filter: Option<PairFilter>,
+    // a sensor only reports the overlaps, no contact is created for it
+    pub is_sensor: bool,
+    pub material: Material,
 }
 
 impl CollisionObject {
@@ -17,6 +38,49 @@ impl CollisionObject {
             shape: shape,
             rigidbody_id: None,
             is_static: false,
+            is_kinematic: false,
+            membership: ALL_GROUPS,
+            filter: ALL_GROUPS,
+            pair_filter: None,
+            is_sensor: false,
+            material: Material::default(),
+        }
+    }
+
+    pub fn new_sensor(shape: Box<dyn Shape>) -> CollisionObject {
+        let mut co = CollisionObject::new(shape);
+        co.is_sensor = true;
+        co
+    }
+
+    /**
+     * Neither static nor kinematic, it is moved by the contacts
+     */
+    pub fn is_dynamic(&self) -> bool {
+        !self.is_static && !self.is_kinematic
+    }
+
+    /**
+     * Mass of the shape filled with the density of the material.
+     */
+    pub fn mass_from_density(&self) -> Real {
+        self.material.density * self.shape.volume()
+    }
+
+    pub fn set_collision_groups(&mut self, membership: u32, filter: u32) {
+        self.membership = membership;
+        self.filter = filter;
+    }
+
+    /**
+     * Both objects must be in a group the other one filters,
+     * then the pair filter of each object (if any) must accept the pair.
+     */
+    pub fn can_collide_with(&self, o: &CollisionObject) -> bool {
+        if self.membership & o.filter == 0 || o.membership & self.filter == 0 {
+            return false;
         }
+
+        self.pair_filter.is_none_or(|f| f(self, o)) && o.pair_filter.is_none_or(|f| f(o, self))
     }
 }
@@ -0,0 +1,119 @@
+use crate::math::math_essentials::*;
+
+/**
+ * How the coefficients of the two materials of a contact give the coefficient of the pair.
+ * When the two materials use different rules, the rule coming last in this enum is used
+ * (Average < Min < Multiply < Max).
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineRule {
+    Average = 0,
+    Min = 1,
+    Multiply = 2,
+    Max = 3,
+}
+
+impl CombineRule {
+    pub fn combine(rule_a: CombineRule, rule_b: CombineRule, a: Real, b: Real) -> Real {
+        match std::cmp::max(rule_a, rule_b) {
+            CombineRule::Average => (a + b) / TWO,
+            CombineRule::Min => helper::min(a, b),
+            CombineRule::Multiply => a * b,
+            CombineRule::Max => helper::max(a, b),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Material {
+    pub static_friction: Real,
+    pub dynamic_friction: Real,
+    pub restitution: Real,
+    // kg.m^-3
+    pub density: Real,
+    pub friction_combine_rule: CombineRule,
+    pub restitution_combine_rule: CombineRule,
+}
+
+impl Material {
+    pub fn new(
+        static_friction: Real,
+        dynamic_friction: Real,
+        restitution: Real,
+        density: Real,
+    ) -> Material {
+        Material {
+            static_friction,
+            dynamic_friction,
+            restitution,
+            density,
+            friction_combine_rule: CombineRule::Average,
+            restitution_combine_rule: CombineRule::Average,
+        }
+    }
+
+    pub fn with_combine_rules(
+        mut self,
+        friction_combine_rule: CombineRule,
+        restitution_combine_rule: CombineRule,
+    ) -> Material {
+        self.friction_combine_rule = friction_combine_rule;
+        self.restitution_combine_rule = restitution_combine_rule;
+        self
+    }
+
+    /**
+     * Coefficients of a contact between `self` and `other`: (static friction, dynamic friction, restitution)
+     */
+    pub fn combine(&self, other: &Material) -> (Real, Real, Real) {
+        (
+            CombineRule::combine(
+                self.friction_combine_rule,
+                other.friction_combine_rule,
+                self.static_friction,
+                other.static_friction,
+            ),
+            CombineRule::combine(
+                self.friction_combine_rule,
+                other.friction_combine_rule,
+                self.dynamic_friction,
+                other.dynamic_friction,
+            ),
+            CombineRule::combine(
+                self.restitution_combine_rule,
+                other.restitution_combine_rule,
+                self.restitution,
+                other.restitution,
+            ),
+        )
+    }
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material::new(0.6, 0.4, 0.95, 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_rules() {
+        let ice = Material::new(0.1, 0.05, 0.2, 900.0);
+        let rubber = Material::new(0.9, 0.8, 0.8, 1100.0)
+            .with_combine_rules(CombineRule::Max, CombineRule::Multiply);
+
+        // the rule of rubber wins, whatever the order
+        assert_eq!(ice.combine(&rubber), (0.9, 0.8, 0.8 * 0.2));
+        assert_eq!(rubber.combine(&ice), (0.9, 0.8, 0.8 * 0.2));
+
+        let ice_min = ice.with_combine_rules(CombineRule::Min, CombineRule::Min);
+        assert_eq!(ice_min.combine(&Material::default()), (0.1, 0.05, 0.2));
+        assert_eq!(
+            CombineRule::combine(CombineRule::Average, CombineRule::Average, 0.2, 0.4),
+            0.3 as Real
+        );
+    }
+}
@@ -1,33 +1,83 @@
 pub mod collision_object;
+pub mod material;
 
 pub use collision_object::CollisionObject;
+pub use material::{CombineRule, Material};
 
+use crate::engine::broadphase::{BroadPhase, BroadPhaseType};
 use crate::engine::contact_algorithms::ContactManifold;
-use crate::engine::intersection_algorithms::intersection_wrapper::get_intersection_fn_by_collisiontypes;
+use crate::engine::intersection_algorithms::intersection_wrapper::{
+    get_intersection_fn_by_collisiontypes, get_overlap_fn_by_collisiontypes,
+};
+use crate::geometry::aabb::Aabb;
 use crate::math::math_essentials::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::vec::Vec;
 
+/**
+ * Overlap between a sensor and another CollisionObject
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SensorOverlap {
+    pub id_collision_object_a: usize,
+    pub id_collision_object_b: usize,
+}
+
 pub struct CollisionWorld {
     pub collision_objects: HashMap<usize, CollisionObject>,
     pub contact_manifolds: Vec<ContactManifold>,
+    pub sensor_overlaps: Vec<SensorOverlap>,
+    // manifolds of the previous step, by pair of collision objects
+    persistent_manifolds: HashMap<(usize, usize), ContactManifold>,
+    // pairs of collision objects that never collide, the smallest id first
+    ignored_pairs: HashSet<(usize, usize)>,
+    broadphase: Box<dyn BroadPhase>,
+    broadphase_type: BroadPhaseType,
     id_counter: usize, // used to give a number to each CollisionObject
 }
 
 impl CollisionWorld {
+    /**
+     * Use a sweep and prune broadphase
+     */
     pub fn new() -> Self {
+        CollisionWorld::with_broadphase(BroadPhaseType::SweepAndPrune)
+    }
+
+    pub fn with_broadphase(broadphase_type: BroadPhaseType) -> Self {
         Self {
             collision_objects: HashMap::new(),
             contact_manifolds: Vec::new(),
+            sensor_overlaps: Vec::new(),
+            persistent_manifolds: HashMap::new(),
+            ignored_pairs: HashSet::new(),
+            broadphase: broadphase_type.create(),
+            broadphase_type,
             id_counter: 0,
         }
     }
 
+    pub fn broadphase_type(&self) -> BroadPhaseType {
+        self.broadphase_type
+    }
+
+    /**
+     * Change the broadphase, the objects already in the world are inserted into the new one.
+     */
+    pub fn set_broadphase(&mut self, broadphase_type: BroadPhaseType) {
+        self.broadphase = broadphase_type.create();
+        self.broadphase_type = broadphase_type;
+        for (id, o) in &self.collision_objects {
+            self.broadphase.insert(*id, o.shape.aabb());
+        }
+    }
+
     pub fn add_collision_object(&mut self, mut o: CollisionObject) -> usize {
         let id = self.id_counter;
         self.id_counter += 1;
         o.id = id;
+        self.broadphase.insert(id, o.shape.aabb());
         self.collision_objects.insert(id, o);
         id
     }
@@ -50,6 +100,7 @@ impl CollisionWorld {
         match self.collision_objects.get_mut(&id) {
             Some(x) => {
                 x.shape.set_transform(transform);
+                self.broadphase.update(id, x.shape.aabb());
             }
             None => {
                 panic!("update_collision_object(id), the pair key-value doesn't exist !, the collision object isnt");
@@ -57,6 +108,35 @@ impl CollisionWorld {
         }
     }
 
+    /**
+     * An ignored pair never goes through the narrowphase, e.g. two bodies linked by a joint.
+     */
+    pub fn set_pair_ignored(&mut self, id_a: usize, id_b: usize, ignored: bool) {
+        let key = (id_a.min(id_b), id_a.max(id_b));
+        if ignored {
+            self.ignored_pairs.insert(key);
+        } else {
+            self.ignored_pairs.remove(&key);
+        }
+    }
+
+    pub fn is_pair_ignored(&self, id_a: usize, id_b: usize) -> bool {
+        self.ignored_pairs
+            .contains(&(id_a.min(id_b), id_a.max(id_b)))
+    }
+
+    /**
+     * Ids of the CollisionObjects whose bounds overlap `aabb`,
+     * the broadphase is used as the acceleration structure.
+     */
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        self.broadphase
+            .query_aabb(aabb)
+            .into_iter()
+            .filter(|id| self.collision_objects[id].shape.aabb().intersects(aabb))
+            .collect()
+    }
+
     /**
      * No checks on the ids provided
      */
@@ -79,35 +159,85 @@ impl CollisionWorld {
         let algo = get_intersection_fn_by_collisiontypes(shape_i, shape_j)?;
         let contact_infos = algo(shape_i, shape_j)?;
 
-        let cm = ContactManifold {
-            id_collision_object_a: id1_collision_object,
-            id_collision_object_b: id2_collision_object,
-            contact_infos,
-        };
+        let cm = ContactManifold::new(id1_collision_object, id2_collision_object, contact_infos);
         Some(cm)
     }
 
+    /**
+     * No checks on the ids provided.
+     * Only tells if the shapes overlap, without computing the contact.
+     */
+    pub fn are_overlapping(
+        &self,
+        id1_collision_object: usize,
+        id2_collision_object: usize,
+    ) -> bool {
+        let shape_i = self.collision_objects[&id1_collision_object].shape.as_ref();
+        let shape_j = self.collision_objects[&id2_collision_object].shape.as_ref();
+
+        match get_overlap_fn_by_collisiontypes(shape_i, shape_j) {
+            Some(algo) => algo(shape_i, shape_j),
+            None => false,
+        }
+    }
+
+    /**
+     * Only the pairs whose AABBs overlap in the broadphase and whose collision groups
+     * and pair filters accept each other, and that are not ignored, go through the narrowphase.
+     * The pairs with a sensor only get an overlap test and fill `sensor_overlaps`,
+     * the other pairs need a dynamic object to be solved.
+     */
     pub fn step(&mut self) {
-        for (i1, el1) in self.collision_objects.iter().enumerate() {
-            let obj_i = el1.1;
-            if !obj_i.enabled {
+        for (id_i, id_j) in self.broadphase.compute_pairs() {
+            let obj_i = &self.collision_objects[&id_i];
+            let obj_j = &self.collision_objects[&id_j];
+            if !obj_i.enabled
+                || !obj_j.enabled
+                || (obj_i.is_sensor && obj_j.is_sensor)
+                || (!obj_i.is_sensor
+                    && !obj_j.is_sensor
+                    && !obj_i.is_dynamic()
+                    && !obj_j.is_dynamic())
+                || !obj_i.can_collide_with(obj_j)
+                || self.is_pair_ignored(id_i, id_j)
+            {
                 continue;
             }
 
-            for el2 in self.collision_objects.iter().skip(i1 + 1) {
-                let obj_j = el2.1;
-                if !obj_j.enabled || (obj_i.is_static && obj_j.is_static) {
-                    continue;
+            if obj_i.is_sensor || obj_j.is_sensor {
+                if self.are_overlapping(id_i, id_j) {
+                    self.sensor_overlaps.push(SensorOverlap {
+                        id_collision_object_a: id_i,
+                        id_collision_object_b: id_j,
+                    });
                 }
-
-                if let Some(cm) = self.are_colliding(obj_i.id, obj_j.id) {
-                    self.contact_manifolds.push(cm);
+            } else if let Some(mut cm) = self.are_colliding(id_i, id_j) {
+                if let Some(previous) = self.persistent_manifolds.get(&(id_i, id_j)) {
+                    cm.warm_start_from(previous);
                 }
+                self.contact_manifolds.push(cm);
             }
         }
     }
 
+    /**
+     * The manifold of the pair at the previous step, if they were in contact.
+     */
+    pub fn persistent_manifold(&self, id_a: usize, id_b: usize) -> Option<&ContactManifold> {
+        self.persistent_manifolds
+            .get(&(id_a, id_b))
+            .or_else(|| self.persistent_manifolds.get(&(id_b, id_a)))
+    }
+
+    /**
+     * The manifolds of the step are kept, by pair, until the next step to warm start the solver.
+     */
     pub fn clear_manifold(&mut self) {
-        self.contact_manifolds.clear();
+        self.persistent_manifolds.clear();
+        for cm in self.contact_manifolds.drain(..) {
+            self.persistent_manifolds
+                .insert((cm.id_collision_object_a, cm.id_collision_object_b), cm);
+        }
+        self.sensor_overlaps.clear();
     }
 }
@@ -18,10 +18,82 @@ pub struct ContactManifold {
     pub id_collision_object_a: usize,
     pub id_collision_object_b: usize,
     pub contact_infos: ContactInformations,
+    // one per point of contact_infos, what the solver applied on the point during the step
+    pub accumulated_impulses: Vec<AccumulatedImpulse>,
+}
+
+impl ContactManifold {
+    pub fn new(
+        id_collision_object_a: usize,
+        id_collision_object_b: usize,
+        contact_infos: ContactInformations,
+    ) -> ContactManifold {
+        let accumulated_impulses = vec![AccumulatedImpulse::default(); contact_infos.points.len()];
+        ContactManifold {
+            id_collision_object_a,
+            id_collision_object_b,
+            contact_infos,
+            accumulated_impulses,
+        }
+    }
+
+    /**
+     * Reuses the accumulated impulses of the points of the manifold of the previous step
+     * produced by the same features, the other points start from zero.
+     */
+    pub fn warm_start_from(&mut self, previous: &ContactManifold) {
+        for (feature_id, impulse) in self
+            .contact_infos
+            .feature_ids
+            .iter()
+            .zip(self.accumulated_impulses.iter_mut())
+        {
+            if let Some(i) = previous
+                .contact_infos
+                .feature_ids
+                .iter()
+                .position(|f| f == feature_id)
+            {
+                *impulse = previous.accumulated_impulses[i];
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AccumulatedImpulse {
+    pub normal: Real,
+    // along the two tangents of the solver
+    pub tangent: [Real; 2],
+}
+
+/**
+ * Which features of the two shapes produced a contact point.
+ * A point keeping the same id from a step to another is considered to be the same contact.
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FeatureId {
+    // vertex of the incident face clipped on the reference face, from sat::FaceResult
+    Face {
+        reference_face: usize,
+        incident_face: usize,
+        incident_vertex: usize,
+    },
+    // closest points of two edges, from sat::EdgeResult
+    Edge {
+        edge_a: usize,
+        edge_b: usize,
+    },
+    // vertex of a polyhedron against a plane
+    Vertex(usize),
+    // the only point of a contact involving a sphere
+    Smooth,
 }
 
 pub struct ContactInformations {
     pub points: Vec<P3>,
+    // one per point
+    pub feature_ids: Vec<FeatureId>,
     pub normal_a_to_b: Vec3,
     pub penetration_distance: Real,
 }
@@ -1,36 +1,65 @@
-use super::ContactInformations;
+use super::{ContactInformations, FeatureId};
 
-use crate::engine::shapes::{Segment, OBB};
+use crate::engine::shapes::{Segment, Shape, OBB};
 use crate::geometry::{self, geometry_traits::*, sat, sat::EdgeResult, sat::FaceResult};
 use crate::math::math_essentials::*;
 
+// the face contacts are preferred over the edge contacts, to keep the same features from a step to another
+const FACE_RELATIVE_TOLERANCE: Real = 0.95;
+const FACE_ABSOLUTE_TOLERANCE: Real = 0.005;
+
 pub fn obb_obb(obb1: &OBB, obb2: &OBB) -> ContactInformations {
-    let sat_result = sat::sat_3D(obb1, obb2).unwrap();
+    let mut sat_result = sat::sat_3D(obb1, obb2).unwrap();
 
     let normal: Vec3;
     let distance: Real;
     let points: Vec<P3>;
-    if sat_result.face_A.distance <= sat_result.edge.distance
-        && sat_result.face_B.distance <= sat_result.edge.distance
+    let feature_ids: Vec<FeatureId>;
+    if sat_result.face_A.distance * FACE_RELATIVE_TOLERANCE
+        <= sat_result.edge.distance + FACE_ABSOLUTE_TOLERANCE
+        && sat_result.face_B.distance * FACE_RELATIVE_TOLERANCE
+            <= sat_result.edge.distance + FACE_ABSOLUTE_TOLERANCE
     {
         // Face Contact
+        // the SAT only tests one face per axis, the reference face is the one facing obb2
+        if dot(
+            &sat_result.face_A.axis,
+            &(obb2.get_position() - obb1.get_position()),
+        ) < ZERO
+        {
+            sat_result.face_A.axis = -sat_result.face_A.axis;
+            sat_result.face_A.face_index += 1;
+        }
         normal = sat_result.face_A.axis;
         distance = sat_result.face_A.distance;
-        points = face_contact(&obb1, &obb2, &sat_result.face_A);
+        (points, feature_ids) = face_contact(&obb1, &obb2, &sat_result.face_A);
     } else {
         // Edge Contact
         normal = sat_result.edge.axis;
         distance = sat_result.edge.distance;
         points = edge_contact(&obb1, &obb2, &sat_result.edge);
+        feature_ids = vec![FeatureId::Edge {
+            edge_a: sat_result.edge.edge_a_index,
+            edge_b: sat_result.edge.edge_b_index,
+        }];
     }
     ContactInformations {
         points,
+        feature_ids,
         normal_a_to_b: normal,
         penetration_distance: distance,
     }
 }
 
-pub fn face_contact(obb1: &OBB, obb2: &OBB, reference_face: &FaceResult) -> Vec<P3> {
+/**
+ * The clipping only moves the vertices of the incident face, each point keeps
+ * the index of the vertex it comes from in its FeatureId.
+ */
+pub fn face_contact(
+    obb1: &OBB,
+    obb2: &OBB,
+    reference_face: &FaceResult,
+) -> (Vec<P3>, Vec<FeatureId>) {
     // face de obb1
     let reference_face_normal = reference_face.axis;
 
@@ -54,9 +83,16 @@ pub fn face_contact(obb1: &OBB, obb2: &OBB, reference_face: &FaceResult) -> Vec<
     }
     // on récupère les vertex de la face adjacente
     let mut vertices_to_clip = Vec::<P3>::new();
+    let mut feature_ids = Vec::<FeatureId>::new();
     vertices_to_clip.reserve(obb2.faces_ref()[adjacent_face_index].v_i.len());
+    feature_ids.reserve(obb2.faces_ref()[adjacent_face_index].v_i.len());
     for i in &obb2.faces_ref()[adjacent_face_index].v_i {
         vertices_to_clip.push(obb2_vertices[*i]);
+        feature_ids.push(FeatureId::Face {
+            reference_face: reference_face.face_index,
+            incident_face: adjacent_face_index,
+            incident_vertex: *i,
+        });
     }
 
     // on clip pour chaque côté de la face de référence les vertices_to_clip
@@ -84,7 +120,7 @@ pub fn face_contact(obb1: &OBB, obb2: &OBB, reference_face: &FaceResult) -> Vec<
         &obb1_vertices[obb1.faces_ref()[reference_face.face_index].v_i[0]],
     );
 
-    vertices_to_clip
+    (vertices_to_clip, feature_ids)
 }
 
 pub fn edge_contact(obb1: &OBB, obb2: &OBB, edge: &EdgeResult) -> Vec<P3> {
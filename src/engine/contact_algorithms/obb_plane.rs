@@ -1,4 +1,4 @@
-use super::ContactInformations;
+use super::{ContactInformations, FeatureId};
 use crate::engine::shapes::{Plane, Segment, Shape, OBB};
 use crate::geometry::{
     geometry_traits::{FaceIndex, PolyhedronTrait},
@@ -9,13 +9,15 @@ use crate::math::{math_essentials::*, Quaternion};
 pub fn obb_plane(obb: &OBB, plane: &Plane) -> ContactInformations {
     let mut distance = ZERO;
     let mut points = Vec::<P3>::new();
+    let mut feature_ids = Vec::<FeatureId>::new();
 
-    for v in &obb.transformed_vertices() {
+    for (i, v) in obb.transformed_vertices().iter().enumerate() {
         let d = plane.signed_distance(&v);
         // si en desous du plan
         if d < ZERO {
             // on remonte le point a mi chemin du plan
             points.push(v - &(plane.normal * d * 0.5));
+            feature_ids.push(FeatureId::Vertex(i));
             if d < distance {
                 distance = d;
             }
@@ -24,6 +26,7 @@ pub fn obb_plane(obb: &OBB, plane: &Plane) -> ContactInformations {
 
     ContactInformations {
         points,
+        feature_ids,
         normal_a_to_b: -plane.normal,
         penetration_distance: distance.abs(),
     }
@@ -73,6 +76,7 @@ pub fn azazeazeobb_plane(obb: &OBB, plane: &Plane) -> ContactInformations {
     }
 
     ContactInformations {
+        feature_ids: (0..points.len()).map(FeatureId::Vertex).collect(),
         points,
         normal_a_to_b: -plane.normal,
         penetration_distance,
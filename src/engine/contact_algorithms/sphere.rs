@@ -1,4 +1,4 @@
-use super::ContactInformations;
+use super::{ContactInformations, FeatureId};
 
 use crate::engine::shapes::Sphere;
 use crate::math::{vector::*, Real, Vec3, P3};
@@ -13,6 +13,7 @@ pub fn sphere_sphere(s1: &Sphere, s2: &Sphere) -> ContactInformations {
     let dtp = s1.radius - penetration_distance;
     let p = s1.position + n * dtp;
     ContactInformations {
+        feature_ids: vec![FeatureId::Smooth],
         points: vec![p],
         normal_a_to_b: n,
         penetration_distance,
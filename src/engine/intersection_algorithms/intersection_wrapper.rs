@@ -1,6 +1,7 @@
 use crate::engine::contact_algorithms;
 use crate::engine::contact_algorithms::ContactInformations;
 use crate::engine::shapes::{Plane, Shape, Sphere, OBB};
+use crate::geometry::sat;
 use crate::math::Vec3;
 
 type FuncType = fn(&Box<dyn Shape>, &Box<dyn Shape>) -> Option<ContactInformations>;
@@ -47,6 +48,38 @@ pub fn get_intersection_fn_by_collisiontypes(
     INTERSECTIONS_FUNCTIONS_BY_SHAPE_TYPE[i1][i2]
 }
 
+type OverlapFuncType = fn(&dyn Shape, &dyn Shape) -> bool;
+const OVERLAP_FUNCTIONS_BY_SHAPE_TYPE: [[Option<OverlapFuncType>; N_SHAPES]; N_SHAPES] = [
+    //Sphere = 0
+    [
+        Some(overlap_sphere_sphere),
+        Some(overlap_sphere_obb),
+        Some(overlap_sphere_plane),
+    ],
+    // OBB = 1
+    [
+        Some(overlap_obb_sphere),
+        Some(overlap_obb_obb),
+        Some(overlap_obb_plane),
+    ],
+    // Plane = 2
+    [
+        Some(overlap_plane_sphere),
+        Some(overlap_plane_obb),
+        Some(overlap_plane_plane),
+    ],
+];
+
+/**
+ * Same as `get_intersection_fn_by_collisiontypes` but the algorithm only tells if the shapes overlap,
+ * no contact informations are computed (used by the sensors).
+ */
+pub fn get_overlap_fn_by_collisiontypes(s1: &dyn Shape, s2: &dyn Shape) -> Option<OverlapFuncType> {
+    let i1 = s1.shape_type() as usize;
+    let i2 = s2.shape_type() as usize;
+    OVERLAP_FUNCTIONS_BY_SHAPE_TYPE[i1][i2]
+}
+
 /**
  * Dans les cas ou les arguments shape sont dans un sens et sont intervertis car la fonction d'intersection
  * et de contact les prends dans l'autre sens,dans ce cas on swap la normal pour qu'elle pointe toujours de a vers b.
@@ -236,3 +269,85 @@ fn compute_sphere_plane(
         None
     }
 }
+
+fn overlap_sphere_sphere(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<Sphere>()
+        .expect("Tried to downcast to Sphere");
+    let o2 = shape2
+        .downcast_ref::<Sphere>()
+        .expect("Tried to downcast to Sphere");
+
+    super::sphere::sphere_sphere(o1, o2)
+}
+
+/**
+ * The quick test of intersection_algorithms::obb::obb_obb can give false positives,
+ * the SAT is used instead.
+ */
+fn overlap_obb_obb(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<OBB>()
+        .expect("Tried to downcast to OBB");
+    let o2 = shape2
+        .downcast_ref::<OBB>()
+        .expect("Tried to downcast to OBB");
+
+    sat::sat_3D(o1, o2).is_some()
+}
+
+fn overlap_plane_plane(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<Plane>()
+        .expect("Tried to downcast to Plane");
+    let o2 = shape2
+        .downcast_ref::<Plane>()
+        .expect("Tried to downcast to Plane");
+
+    super::plane::plane_plane(o1, o2)
+}
+
+fn overlap_sphere_obb(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    overlap_obb_sphere(shape2, shape1)
+}
+
+fn overlap_obb_sphere(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<OBB>()
+        .expect("Tried to downcast to OBB");
+    let o2 = shape2
+        .downcast_ref::<Sphere>()
+        .expect("Tried to downcast to Sphere");
+
+    super::obb_sphere::obb_sphere(o1, o2)
+}
+
+fn overlap_obb_plane(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<OBB>()
+        .expect("Tried to downcast to OBB");
+    let o2 = shape2
+        .downcast_ref::<Plane>()
+        .expect("Tried to downcast to Plane");
+
+    super::obb_plane::obb_plane(o1, o2)
+}
+
+fn overlap_plane_obb(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    overlap_obb_plane(shape2, shape1)
+}
+
+fn overlap_plane_sphere(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    let o1 = shape1
+        .downcast_ref::<Plane>()
+        .expect("Tried to downcast to Plane");
+    let o2 = shape2
+        .downcast_ref::<Sphere>()
+        .expect("Tried to downcast to Sphere");
+
+    super::plane_sphere::plane_sphere(o1, o2)
+}
+
+fn overlap_sphere_plane(shape1: &dyn Shape, shape2: &dyn Shape) -> bool {
+    overlap_plane_sphere(shape2, shape1)
+}
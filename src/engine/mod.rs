@@ -1,3 +1,4 @@
+pub mod broadphase;
 pub mod collision;
 pub mod intersection_algorithms;
 pub mod shapes;
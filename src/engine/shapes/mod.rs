@@ -3,6 +3,7 @@ pub mod plane;
 pub mod segment;
 pub mod sphere;
 
+use crate::geometry::aabb::Aabb;
 use crate::math::{math_essentials::*, Mat3};
 use downcast_rs::DowncastSync;
 
@@ -23,6 +24,11 @@ pub trait Shape: DowncastSync {
     fn shape_type(&self) -> ShapeType;
     fn is_rigid_body(&self) -> bool;
     fn compute_inertia_matrix(&self, mass: Real) -> Mat3;
+    fn volume(&self) -> Real;
+    /**
+     * Tight world space bounds of the shape if it was placed at `transform`.
+     */
+    fn compute_aabb(&self, transform: &Transform) -> Aabb;
 
     fn get_position(&self) -> &P3;
     fn get_orientation(&self) -> &Mat3;
@@ -31,6 +37,25 @@ pub trait Shape: DowncastSync {
     fn set_orientation(&mut self, o: Mat3);
     fn set_transform(&mut self, t: Transform);
 
+    /**
+     * Tight world space bounds of the shape at its current transform.
+     */
+    fn aabb(&self) -> Aabb {
+        self.compute_aabb(&self.get_transform())
+    }
+
+    /**
+     * Bounds of the shape moving from its current transform by `delta`
+     * (rotation around its position then translation, see Transform::mul).
+     * The bounds at both ends of the motion are merged, it is exact for a translation
+     * and close enough for the small rotations of a step.
+     */
+    fn swept_aabb(&self, delta: &Transform) -> Aabb {
+        let current = self.get_transform();
+        self.compute_aabb(&current)
+            .merged(&self.compute_aabb(&(delta * &current)))
+    }
+
     // fn transform_ref(&self) -> &Transform;
     // fn set_transform(&self, t: Transform);
 }
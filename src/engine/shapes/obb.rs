@@ -1,5 +1,5 @@
 use super::{Shape, ShapeType};
-use crate::geometry::{geometry_traits::*, sat::SAT};
+use crate::geometry::{aabb::Aabb, geometry_traits::*, sat::SAT};
 use crate::math::{math_essentials::*, Mat3};
 /**
  * Oriented Bounding Box
@@ -32,6 +32,25 @@ impl Shape for OBB {
         Mat3::diag(diag)
     }
 
+    fn volume(&self) -> Real {
+        8.0 * self.half_side.x() * self.half_side.y() * self.half_side.z()
+    }
+
+    /**
+     * The half extent on each world axis is the sum of the half sides
+     * projected on this axis (same as transforming the vertices).
+     */
+    fn compute_aabb(&self, transform: &Transform) -> Aabb {
+        let r = &transform.rotation;
+        let mut half_extents = Vec3::zeros();
+        for i in 0..3 {
+            half_extents[i] = r[i][0].abs() * self.half_side[0]
+                + r[i][1].abs() * self.half_side[1]
+                + r[i][2].abs() * self.half_side[2];
+        }
+        Aabb::from_center_half_extents(transform.translation, half_extents)
+    }
+
     fn shape_type(&self) -> ShapeType {
         ShapeType::OBB
     }
@@ -337,6 +356,7 @@ mod tests {
     use crate::engine::shapes::Shape;
     use crate::geometry::geometry_traits::PolyhedronTrait;
     use crate::math::{math_essentials::*, Mat3, Quaternion};
+    use assert_approx_eq::assert_approx_eq;
 
     #[test]
     fn test_distance_to_contour_in_direction() {
@@ -530,6 +550,38 @@ mod tests {
         println!("{:?} {:?}", obb.face_normal(5), obb.computed_face_normal(5));
     }
 
+    #[test]
+    fn obb_aabb() {
+        let mut obb = OBB::new(Vec3::new(2.0, ONE, ONE));
+        obb.set_transform(Transform::new(
+            Vec3::ones(),
+            Rotation::Z(helper::angle_2_rad(90.0)),
+            Vec3::new(ONE, ZERO, ZERO),
+        ));
+        let aabb = obb.aabb();
+
+        // same bounds as the transformed vertices
+        let vertices = obb.transformed_vertices();
+        for i in 0..3 {
+            let min = vertices.iter().map(|v| v[i]).fold(Real::MAX, Real::min);
+            let max = vertices.iter().map(|v| v[i]).fold(Real::MIN, Real::max);
+            assert_approx_eq!(aabb.min[i], min, 1.0e-6);
+            assert_approx_eq!(aabb.max[i], max, 1.0e-6);
+        }
+        assert_approx_eq!(aabb.max[1], 2.0, 1.0e-6);
+
+        // rotated of 90 degrees again by the delta, the box ends up as it was at the start
+        let swept = obb.swept_aabb(&Transform::new(
+            Vec3::ones(),
+            Rotation::Z(helper::angle_2_rad(90.0)),
+            Vec3::new(ZERO, 3.0, ZERO),
+        ));
+        assert_approx_eq!(swept.min[0], -ONE, 1.0e-6);
+        assert_approx_eq!(swept.max[0], 3.0, 1.0e-6);
+        assert_approx_eq!(swept.min[1], -2.0, 1.0e-6);
+        assert_approx_eq!(swept.max[1], 4.0, 1.0e-6);
+    }
+
     #[test]
     fn normal_face_test() {
         let mut obb = OBB::new(Vec3::new(ONE, ONE, ONE));
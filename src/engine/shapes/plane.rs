@@ -1,4 +1,5 @@
 use super::{Shape, ShapeType};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::geometry_traits::PolyhedronTrait;
 use crate::math::{math_essentials::*, Mat3};
 
@@ -76,6 +77,17 @@ impl Shape for Plane {
         panic!("inertia matrix for Plane not implemented");
     }
 
+    fn volume(&self) -> Real {
+        Real::INFINITY
+    }
+
+    /**
+     * The plane is a half-space, its bounds are infinite
+     */
+    fn compute_aabb(&self, _transform: &Transform) -> Aabb {
+        Aabb::infinite()
+    }
+
     fn shape_type(&self) -> ShapeType {
         ShapeType::Plane
     }
@@ -1,4 +1,5 @@
 use super::{Plane, Shape};
+use crate::geometry::aabb::Aabb;
 use crate::math::math_essentials::*;
 
 pub struct Segment {
@@ -11,6 +12,24 @@ impl Segment {
         Segment { a, b }
     }
 
+    /**
+     * The segment isn't a Shape of the CollisionWorld, its points are already in world space.
+     */
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(
+            P3::new(
+                helper::min(self.a[0], self.b[0]),
+                helper::min(self.a[1], self.b[1]),
+                helper::min(self.a[2], self.b[2]),
+            ),
+            P3::new(
+                helper::max(self.a[0], self.b[0]),
+                helper::max(self.a[1], self.b[1]),
+                helper::max(self.a[2], self.b[2]),
+            ),
+        )
+    }
+
     pub fn is_point_on_line(&self, p: P3) -> bool {
         let ap = normalized(p - self.a);
         let ab = normalized(self.b - self.a);
@@ -175,6 +194,14 @@ mod tests {
     use super::*;
     use assert_approx_eq::assert_approx_eq;
 
+    #[test]
+    fn test_segment_aabb() {
+        let s = Segment::new(P3::new(ONE, -2.0, 3.0), P3::new(-ONE, 2.0, 3.0));
+        let aabb = s.aabb();
+        assert_eq!(aabb.min, P3::new(-ONE, -2.0, 3.0));
+        assert_eq!(aabb.max, P3::new(ONE, 2.0, 3.0));
+    }
+
     #[test]
     fn test_intersect_plane() {
         {
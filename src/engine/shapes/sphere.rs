@@ -1,4 +1,5 @@
 use super::{Shape, ShapeType};
+use crate::geometry::aabb::Aabb;
 use crate::math::{math_essentials::*, Mat3};
 
 pub struct Sphere {
@@ -31,6 +32,14 @@ impl Shape for Sphere {
         Mat3::diag(Vec3::value(0.4 * mass * self.radius.powi(2)))
     }
 
+    fn volume(&self) -> Real {
+        4.0 / 3.0 * std::f32::consts::PI * self.radius.powi(3)
+    }
+
+    fn compute_aabb(&self, transform: &Transform) -> Aabb {
+        Aabb::from_center_half_extents(transform.translation, Vec3::value(self.radius))
+    }
+
     fn shape_type(&self) -> ShapeType {
         ShapeType::Sphere
     }
@@ -58,3 +67,22 @@ impl Shape for Sphere {
         self.position = t.translation;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sphere_aabb() {
+        let mut s = Sphere::new(2.0);
+        s.set_position(P3::new(ONE, ZERO, ZERO));
+
+        let aabb = s.aabb();
+        assert_eq!(aabb.min, P3::new(-ONE, -2.0, -2.0));
+        assert_eq!(aabb.max, P3::new(3.0, 2.0, 2.0));
+
+        let swept = s.swept_aabb(&Transform::translation(P3::new(ZERO, 5.0, ZERO)));
+        assert_eq!(swept.min, P3::new(-ONE, -2.0, -2.0));
+        assert_eq!(swept.max, P3::new(3.0, 7.0, 2.0));
+    }
+}
@@ -1,261 +1,679 @@
 use super::consts;
+use super::joints::joint_bodies;
 use super::RigidBody;
 use super::SimulationWorld;
-use crate::engine::contact_algorithms::ContactInformations;
+use crate::geometry::helper::perp;
 use crate::math::{math_essentials::*, Quaternion};
+use std::collections::HashSet;
 
-pub struct ContactSolver<'a> {
-    sim: &'a mut SimulationWorld,
-    // penetration
+/**
+ * How the penetration left after the velocity resolution is corrected.
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PositionCorrection {
+    // a velocity bias on the normal impulse, adds energy to the bodies
+    Baumgarte,
+    // the bias is solved on pseudo velocities only integrated into the positions
+    SplitImpulse,
+    // the positions are corrected directly after the integration, from the current penetration
+    NonLinearGaussSeidel,
 }
 
-impl<'a> ContactSolver<'a> {
-    pub fn new(simulation: &'a mut SimulationWorld) -> ContactSolver {
-        ContactSolver { sim: simulation }
+pub struct SolverParameters {
+    pub velocity_iterations: usize,
+    pub position_iterations: usize,
+    pub position_correction: PositionCorrection,
+    // fraction of the penetration corrected per step
+    pub baumgarte_factor: Real,
+    // penetration allowed, keeps the contacts alive from one step to the next
+    pub slop: Real,
+    // maximum penetration corrected per step
+    pub max_correction: Real,
+}
+
+impl Default for SolverParameters {
+    fn default() -> SolverParameters {
+        SolverParameters {
+            velocity_iterations: consts::VELOCITY_ITERATIONS,
+            position_iterations: consts::POSITION_ITERATIONS,
+            position_correction: PositionCorrection::SplitImpulse,
+            baumgarte_factor: consts::BAUMGARTE_FACTOR,
+            slop: consts::PENETRATION_SLOP,
+            max_correction: consts::MAX_PENETRATION_CORRECTION,
+        }
     }
+}
 
-    pub fn pre_compute(&mut self) {
-        
+impl SolverParameters {
+    /**
+     * Penetration to correct this step, without the slop and limited by max_correction
+     */
+    fn position_error(&self, penetration: Real) -> Real {
+        helper::min(
+            helper::max(penetration - self.slop, ZERO),
+            self.max_correction,
+        )
     }
 }
 
 /**
- * Return a triplet, useful in the penetration_resolution() stage
+ * A point of a ContactConstraint, all its quantities are computed once per step in pre_compute().
  */
-pub fn rotation_per_unit_impulse(
-    rigidbody: &RigidBody,
-    point: &P3,
-    normal: &Vec3,
-) -> (Vec3, Vec3, Vec3) {
-    let torque_per_unit_impulse = cross(&point, &normal); // normal is the unit
-    let rotation_per_unit_impulse_v = rigidbody.inv_inertia_tensor() * &torque_per_unit_impulse;
-    let angular_velocity_per_unit_impulse = cross(&rotation_per_unit_impulse_v, &point);
-
-    (
-        torque_per_unit_impulse,
-        rotation_per_unit_impulse_v,
-        angular_velocity_per_unit_impulse,
-    )
-}
-
-pub fn velocity_at_point(rigidbody: &RigidBody, relative_contact_point: &P3) -> Vec3 {
-    rigidbody.linear_velocity + cross(&rigidbody.angular_velocity, &relative_contact_point)
+struct ContactConstraintPoint {
+    // from the center of mass of each body to the point
+    rb1_2_point: Vec3,
+    rb2_2_point: Vec3,
+    // the point in the local frame of each body, to follow it during the position correction
+    local_anchor_1: Vec3,
+    local_anchor_2: Vec3,
+    penetration: Real,
+    // effective mass 1 / (J M^-1 J^T) along the normal and the tangents
+    normal_mass: Real,
+    tangent_mass: [Real; 2],
+    velocity_bias: Real,
+    normal_impulse: Real,
+    tangent_impulse: [Real; 2],
+    pseudo_impulse: Real,
 }
 
 /**
- * Clear source =
- * https://en.wikipedia.org/wiki/Collision_response
+ * What the solver needs from a ContactManifold.
  */
-pub fn velocity_resolution_two_moving(
-    bodies: &mut Vec<RigidBody>,
+struct ContactConstraint {
     rb1_id: usize,
     rb2_id: usize,
-    p: &P3,
-    contact_infos: &ContactInformations,
-) {
-    let rb1 = &bodies[rb1_id];
-    let rb2 = &bodies[rb2_id];
-    let rb1_2_point = p - rb1.center_of_mass();
-    let rb2_2_point = p - rb2.center_of_mass();
-    let normal = &contact_infos.normal_a_to_b;
-    let v1 = velocity_at_point(rb1, &rb1_2_point);
-    let v2 = velocity_at_point(rb2, &rb2_2_point);
-    let rel_velocity = v2 - v1;
-    let rel_velocity_normal = dot(&rel_velocity, &normal);
-
-    // si les deux objets s'eloignent l'un de l'autre alors pas d'impulsion
-    if dot(&normal, &v1) < ZERO && dot(&normal, &v2) > ZERO {
-        return;
-    }
-
-    let (_, _, rot_per_impulse_1) = rotation_per_unit_impulse(rb1, &p, &normal);
-    let (_, _, rot_per_impulse_2) = rotation_per_unit_impulse(rb2, &p, &normal);
-
-    let div =
-        (rb1.inv_mass() + rb2.inv_mass()) + dot(&(rot_per_impulse_1 + rot_per_impulse_2), &normal);
-
-    let t = rel_velocity_normal / div;
+    normal_a_to_b: Vec3,
+    tangents: [Vec3; 2],
+    static_friction_coef: Real,
+    dynamic_friction_coef: Real,
+    points: Vec<ContactConstraintPoint>,
+}
 
-    let imp_a = -(ONE + rb1.restitution_coef) * t;
-    let imp_b = -(ONE + rb2.restitution_coef) * t;
-    let impulsion_vector_a = -normal * imp_a;
-    let impulsion_vector_b = normal * imp_b;
+/**
+ * Sequential impulses solver:
+ * the impulses are accumulated on each point, and the accumulated impulse is clamped
+ * so a point can only push the bodies apart. A static body has a null inverse mass and inertia,
+ * so the same equations handle one or two moving bodies.
+ *
+ * The restitution and friction coefficients of a contact come from the materials of the two CollisionObjects.
+ * The friction impulses, along two tangents, are clamped by the friction cone:
+ * a point sticks while the friction needed stays under static_friction * normal impulse,
+ * otherwise it slides and the friction is dynamic_friction * normal impulse.
+ *
+ * The penetration is then corrected according to SolverParameters::position_correction,
+ * only beyond the slop and by at most max_correction per step.
+ *
+ * The rows of the enabled joints are solved in the same iterations, before the contacts.
+ *
+ * Source : Erin Catto, Iterative Dynamics with Temporal Coherence
+ * https://box2d.org/files/ErinCatto_IterativeDynamics_GDC2005.pdf
+ */
+pub struct ContactSolver<'a> {
+    sim: &'a mut SimulationWorld,
+    constraints: Vec<ContactConstraint>,
+    // split impulses, by rigidbody id
+    pseudo_linear_velocities: Vec<Vec3>,
+    pseudo_angular_velocities: Vec<Vec3>,
+}
 
-    println!("1 linear_imp={:?} ", impulsion_vector_a);
-    println!("2 linear_imp={:?} ", impulsion_vector_b);
+impl<'a> ContactSolver<'a> {
+    pub fn new(simulation: &'a mut SimulationWorld) -> ContactSolver<'a> {
+        ContactSolver {
+            sim: simulation,
+            constraints: Vec::new(),
+            pseudo_linear_velocities: Vec::new(),
+            pseudo_angular_velocities: Vec::new(),
+        }
+    }
 
-    // drop the ref to mut data
-    drop(rb1);
-    drop(rb2);
+    /**
+     * Velocity resolution of the joints and the contact manifolds, the impulses are stored for the next step.
+     * With split impulses, the pseudo velocities are solved too.
+     */
+    pub fn solve(&mut self) {
+        self.pre_compute_joints();
+        self.pre_compute();
+        self.warm_start_joints();
+        self.warm_start();
+        for _ in 0..self.sim.solver_parameters.velocity_iterations {
+            self.solve_joints();
+            self.solve_velocities();
+        }
+        self.store_impulses();
+
+        if self.sim.solver_parameters.position_correction == PositionCorrection::SplitImpulse {
+            let n = self.sim.bodies.len();
+            self.pseudo_linear_velocities = vec![Vec3::zeros(); n];
+            self.pseudo_angular_velocities = vec![Vec3::zeros(); n];
+            for _ in 0..self.sim.solver_parameters.position_iterations {
+                self.solve_split_impulses();
+            }
+        }
+    }
 
-    bodies[rb1_id].apply_linear_impulse(impulsion_vector_a);
-    bodies[rb1_id].apply_angular_impulse(cross(
-        &rb1_2_point,
-        &(impulsion_vector_a * consts::ANGULAR_DAMPING),
-    ));
+    /**
+     * Ids of the bodies in a contact or an enabled joint of the step, moved with their solved velocities
+     */
+    pub fn constrained_bodies(&self) -> HashSet<usize> {
+        let mut ids = HashSet::new();
+        for c in &self.constraints {
+            ids.insert(c.rb1_id);
+            ids.insert(c.rb2_id);
+        }
+        for slot in self.sim.joints.iter().filter(|slot| slot.enabled) {
+            let (rb1_id, rb2_id) = slot.joint.rigidbody_ids();
+            ids.insert(rb1_id);
+            ids.extend(rb2_id);
+        }
+        ids
+    }
 
-    bodies[rb2_id].apply_linear_impulse(impulsion_vector_b);
-    bodies[rb2_id].apply_angular_impulse(cross(
-        &rb2_2_point,
-        &(impulsion_vector_b * consts::ANGULAR_DAMPING),
-    ));
-}
+    pub fn pre_compute_joints(&mut self) {
+        let dt = self.sim.time_step;
+        let baumgarte_factor = self.sim.solver_parameters.baumgarte_factor;
+        for slot in self.sim.joints.iter_mut().filter(|slot| slot.enabled) {
+            let joint = &mut slot.joint;
+            let (rb1_id, rb2_id) = joint.rigidbody_ids();
+            let rb2 = match rb2_id {
+                Some(id) => &self.sim.bodies[id],
+                None => &self.sim.world_body,
+            };
+            joint.pre_compute(&self.sim.bodies[rb1_id], rb2, dt, baumgarte_factor);
+        }
+    }
 
-/**
- *  rb is always the moving object
- *  and the normal must point from the moving object toward the static one,
- *  thus, we need to pass it directly, and not trough the contact_infos object
- * (because we change its direction)
- */
-pub fn velocity_resolution_one_moving(
-    rb: &mut RigidBody,
-    p: &P3,
-    normal: Vec3,
-    penetration_distance: Real,
-) {
-    let center2point = p - rb.center_of_mass();
-    let rel_velocity = velocity_at_point(rb, &center2point);
-    let rel_velocity_normal = dot(&rel_velocity, &normal);
-    println!("==resolve_collision_point_one_moving==");
-
-    println!(
-        "normal {:?} rel_velocity_normal {:?} ",
-        normal, rel_velocity_normal
-    );
-    if rel_velocity_normal < ZERO {
-        return;
+    pub fn warm_start_joints(&mut self) {
+        for slot in self.sim.joints.iter().filter(|slot| slot.enabled) {
+            let joint = &slot.joint;
+            let (rb1_id, rb2_id) = joint.rigidbody_ids();
+            let (rb1, rb2) = joint_bodies(
+                &mut self.sim.bodies,
+                &mut self.sim.world_body,
+                rb1_id,
+                rb2_id,
+            );
+            for row in joint.rows() {
+                row.warm_start(rb1, rb2);
+            }
+        }
     }
 
-    let (_, _, rot_per_impulse) = rotation_per_unit_impulse(rb, &p, &normal);
+    /**
+     * One iteration over the rows of all the joints
+     */
+    pub fn solve_joints(&mut self) {
+        for slot in self.sim.joints.iter_mut().filter(|slot| slot.enabled) {
+            let joint = &mut slot.joint;
+            let (rb1_id, rb2_id) = joint.rigidbody_ids();
+            let (rb1, rb2) = joint_bodies(
+                &mut self.sim.bodies,
+                &mut self.sim.world_body,
+                rb1_id,
+                rb2_id,
+            );
+            for row in joint.rows_mut() {
+                row.solve(rb1, rb2);
+            }
+        }
+    }
 
-    let div = rb.inv_mass() + dot(&rot_per_impulse, &normal);
+    /**
+     * Computes the tangents, the effective masses and the restitution bias of each point of the contact manifolds,
+     * from the velocities before the resolution.
+     * With Baumgarte, the position error is added to the velocity bias.
+     */
+    pub fn pre_compute(&mut self) {
+        let parameters = &self.sim.solver_parameters;
+        let dt = self.sim.time_step;
+        self.constraints.clear();
+        self.constraints
+            .reserve(self.sim.collision_world.contact_manifolds.len());
+
+        for cm in &self.sim.collision_world.contact_manifolds {
+            let (rb1_id, rb2_id) = self.sim.rigidbody_ids(cm);
+            let rb1 = &self.sim.bodies[rb1_id];
+            let rb2 = &self.sim.bodies[rb2_id];
+            let normal = cm.contact_infos.normal_a_to_b;
+            let material_a = &self
+                .sim
+                .collision_world
+                .collision_object_ref(cm.id_collision_object_a)
+                .unwrap()
+                .material;
+            let material_b = &self
+                .sim
+                .collision_world
+                .collision_object_ref(cm.id_collision_object_b)
+                .unwrap()
+                .material;
+            let (static_friction_coef, dynamic_friction_coef, restitution_coef) =
+                material_a.combine(material_b);
+            let (mut tangent_1, mut tangent_2) = perp(&normal);
+            normalize(&mut tangent_1);
+            normalize(&mut tangent_2);
+            let tangents = [tangent_1, tangent_2];
+            let penetration = cm.contact_infos.penetration_distance;
+
+            let mut points = Vec::with_capacity(cm.contact_infos.points.len());
+            for (p, impulse) in cm.contact_infos.points.iter().zip(&cm.accumulated_impulses) {
+                let rb1_2_point = p - rb1.center_of_mass();
+                let rb2_2_point = p - rb2.center_of_mass();
+
+                let rel_velocity =
+                    velocity_at_point(rb2, &rb2_2_point) - velocity_at_point(rb1, &rb1_2_point);
+                let mut velocity_bias =
+                    restitution_velocity_bias(-dot(&rel_velocity, &normal), restitution_coef);
+                if parameters.position_correction == PositionCorrection::Baumgarte {
+                    velocity_bias = helper::max(
+                        velocity_bias,
+                        parameters.baumgarte_factor * parameters.position_error(penetration) / dt,
+                    );
+                }
+
+                points.push(ContactConstraintPoint {
+                    rb1_2_point,
+                    rb2_2_point,
+                    local_anchor_1: rb1.transform.rotation.transposed() * rb1_2_point,
+                    local_anchor_2: rb2.transform.rotation.transposed() * rb2_2_point,
+                    penetration,
+                    normal_mass: effective_mass(rb1, rb2, &rb1_2_point, &rb2_2_point, &normal),
+                    tangent_mass: [
+                        effective_mass(rb1, rb2, &rb1_2_point, &rb2_2_point, &tangents[0]),
+                        effective_mass(rb1, rb2, &rb1_2_point, &rb2_2_point, &tangents[1]),
+                    ],
+                    velocity_bias,
+                    normal_impulse: impulse.normal,
+                    tangent_impulse: impulse.tangent,
+                    pseudo_impulse: ZERO,
+                });
+            }
+
+            self.constraints.push(ContactConstraint {
+                rb1_id,
+                rb2_id,
+                normal_a_to_b: normal,
+                tangents,
+                static_friction_coef,
+                dynamic_friction_coef,
+                points,
+            });
+        }
+    }
 
-    println!(
-        "div={:?} rel_velocity={:?} rel_velo_normal={:?}",
-        div, rel_velocity, rel_velocity_normal
-    );
-    let t = rel_velocity_normal / div;
-    let impulsion = -(ONE + rb.restitution_coef) * t;
-    let impulsion_vector = normal * impulsion;
+    /**
+     * Applies the impulses accumulated on the same points at the previous step.
+     */
+    pub fn warm_start(&mut self) {
+        for c in &self.constraints {
+            for point in &c.points {
+                apply_impulse(
+                    &mut self.sim.bodies,
+                    c.rb1_id,
+                    c.rb2_id,
+                    &point.rb1_2_point,
+                    &point.rb2_2_point,
+                    &(c.normal_a_to_b * point.normal_impulse
+                        + c.tangents[0] * point.tangent_impulse[0]
+                        + c.tangents[1] * point.tangent_impulse[1]),
+                );
+            }
+        }
+    }
 
-    println!("linear_imp={:?} ", impulsion_vector);
+    /**
+     * One iteration over all the points, the friction is solved first
+     * since the normal impulses matter more.
+     */
+    pub fn solve_velocities(&mut self) {
+        for c in &mut self.constraints {
+            for point in &mut c.points {
+                let rb1 = &self.sim.bodies[c.rb1_id];
+                let rb2 = &self.sim.bodies[c.rb2_id];
+                let rel_velocity = velocity_at_point(rb2, &point.rb2_2_point)
+                    - velocity_at_point(rb1, &point.rb1_2_point);
+
+                let previous_impulse = point.tangent_impulse;
+                let mut tangent_impulse = [ZERO; 2];
+                for (i, impulse) in tangent_impulse.iter_mut().enumerate() {
+                    *impulse = previous_impulse[i]
+                        - dot(&rel_velocity, &c.tangents[i]) * point.tangent_mass[i];
+                }
+                // friction cone
+                let norm = (tangent_impulse[0].powi(2) + tangent_impulse[1].powi(2)).sqrt();
+                if norm > c.static_friction_coef * point.normal_impulse {
+                    let scale = c.dynamic_friction_coef * point.normal_impulse / norm;
+                    tangent_impulse[0] *= scale;
+                    tangent_impulse[1] *= scale;
+                }
+                point.tangent_impulse = tangent_impulse;
+
+                apply_impulse(
+                    &mut self.sim.bodies,
+                    c.rb1_id,
+                    c.rb2_id,
+                    &point.rb1_2_point,
+                    &point.rb2_2_point,
+                    &(c.tangents[0] * (tangent_impulse[0] - previous_impulse[0])
+                        + c.tangents[1] * (tangent_impulse[1] - previous_impulse[1])),
+                );
+
+                let rb1 = &self.sim.bodies[c.rb1_id];
+                let rb2 = &self.sim.bodies[c.rb2_id];
+                // negative when the objects are approaching
+                let rel_velocity_normal = dot(
+                    &(velocity_at_point(rb2, &point.rb2_2_point)
+                        - velocity_at_point(rb1, &point.rb1_2_point)),
+                    &c.normal_a_to_b,
+                );
+
+                let previous_impulse = point.normal_impulse;
+                point.normal_impulse = helper::max(
+                    previous_impulse
+                        + (point.velocity_bias - rel_velocity_normal) * point.normal_mass,
+                    ZERO,
+                );
+                let impulse = point.normal_impulse - previous_impulse;
+
+                apply_impulse(
+                    &mut self.sim.bodies,
+                    c.rb1_id,
+                    c.rb2_id,
+                    &point.rb1_2_point,
+                    &point.rb2_2_point,
+                    &(c.normal_a_to_b * impulse),
+                );
+            }
+        }
+    }
 
-    rb.apply_linear_impulse(impulsion_vector);
-    rb.apply_angular_impulse(cross(
-        &center2point,
-        &(impulsion_vector * consts::ANGULAR_DAMPING),
-    ));
-}
+    /**
+     * Writes the accumulated impulses back in the contact manifolds, to warm start the next step.
+     */
+    pub fn store_impulses(&mut self) {
+        for (cm, c) in self
+            .sim
+            .collision_world
+            .contact_manifolds
+            .iter_mut()
+            .zip(&self.constraints)
+        {
+            for (impulse, point) in cm.accumulated_impulses.iter_mut().zip(&c.points) {
+                impulse.normal = point.normal_impulse;
+                impulse.tangent = point.tangent_impulse;
+            }
+        }
+    }
 
-pub fn limit_angular_displacement(
-    mut linear_move: Real,
-    mut angular_move: Real,
-    relative_contact: &Vec3,
-) -> (Real, Real) {
-    let limit = consts::ANGULAR_PENETRATION_DISPLACEMENT_FACTOR * magnitude(relative_contact);
+    /**
+     * One iteration of the split impulses, the normal impulses push the pseudo velocities
+     * toward the velocity correcting the penetration, without touching the real velocities.
+     */
+    pub fn solve_split_impulses(&mut self) {
+        let parameters = &self.sim.solver_parameters;
+        let dt = self.sim.time_step;
+        for c in &mut self.constraints {
+            for point in &mut c.points {
+                let bias =
+                    parameters.baumgarte_factor * parameters.position_error(point.penetration) / dt;
+                let rel_velocity_normal = dot(
+                    &(self.pseudo_linear_velocities[c.rb2_id]
+                        + cross(
+                            &self.pseudo_angular_velocities[c.rb2_id],
+                            &point.rb2_2_point,
+                        )
+                        - self.pseudo_linear_velocities[c.rb1_id]
+                        - cross(
+                            &self.pseudo_angular_velocities[c.rb1_id],
+                            &point.rb1_2_point,
+                        )),
+                    &c.normal_a_to_b,
+                );
+
+                let previous_impulse = point.pseudo_impulse;
+                point.pseudo_impulse = helper::max(
+                    previous_impulse + (bias - rel_velocity_normal) * point.normal_mass,
+                    ZERO,
+                );
+                let impulse = c.normal_a_to_b * (point.pseudo_impulse - previous_impulse);
+
+                let (linear, angular) =
+                    impulse_response(&self.sim.bodies[c.rb1_id], &point.rb1_2_point, &-impulse);
+                self.pseudo_linear_velocities[c.rb1_id] += linear;
+                self.pseudo_angular_velocities[c.rb1_id] += angular;
+                let (linear, angular) =
+                    impulse_response(&self.sim.bodies[c.rb2_id], &point.rb2_2_point, &impulse);
+                self.pseudo_linear_velocities[c.rb2_id] += linear;
+                self.pseudo_angular_velocities[c.rb2_id] += angular;
+            }
+        }
+    }
 
-    if angular_move.abs() > limit {
-        let total_move = linear_move + angular_move;
+    /**
+     * One iteration of nonlinear Gauss-Seidel, the penetration of each point is measured again
+     * from the current positions of the bodies and corrected by moving them directly.
+     */
+    pub fn solve_positions(&mut self) {
+        let parameters = &self.sim.solver_parameters;
+        for c in &self.constraints {
+            for point in &c.points {
+                let rb1 = &self.sim.bodies[c.rb1_id];
+                let rb2 = &self.sim.bodies[c.rb2_id];
+                let rb1_2_point = rb1.transform.rotation * point.local_anchor_1;
+                let rb2_2_point = rb2.transform.rotation * point.local_anchor_2;
+                // the anchors were at the same point when the penetration was computed
+                let separation = dot(
+                    &((rb2.center_of_mass() + &rb2_2_point)
+                        - (rb1.center_of_mass() + &rb1_2_point)),
+                    &c.normal_a_to_b,
+                );
+                let correction = parameters.baumgarte_factor
+                    * parameters.position_error(point.penetration - separation);
+                if correction <= ZERO {
+                    continue;
+                }
+
+                let normal_mass =
+                    effective_mass(rb1, rb2, &rb1_2_point, &rb2_2_point, &c.normal_a_to_b);
+                let impulse = c.normal_a_to_b * (correction * normal_mass);
+
+                let (linear, angular) = impulse_response(rb1, &rb1_2_point, &-impulse);
+                let rb1 = &mut self.sim.bodies[c.rb1_id];
+                rb1.apply_displacement(linear);
+                rb1.apply_rotation_vector(Quaternion::from_vec(&angular));
+                let (linear, angular) =
+                    impulse_response(&self.sim.bodies[c.rb2_id], &rb2_2_point, &impulse);
+                let rb2 = &mut self.sim.bodies[c.rb2_id];
+                rb2.apply_displacement(linear);
+                rb2.apply_rotation_vector(Quaternion::from_vec(&angular));
+            }
+        }
+    }
 
-        if angular_move >= ZERO {
-            angular_move = limit;
-        } else {
-            angular_move = -limit;
+    /**
+     * Integrates the states of the bodies with the velocities of the resolution,
+     * then corrects the penetration according to SolverParameters::position_correction.
+     */
+    pub fn integrate_positions(&mut self) {
+        let dt = self.sim.time_step;
+        for rb in self.sim.bodies.iter_mut().filter(|rb| !rb.is_static) {
+            rb.integrate_state(dt);
         }
 
-        linear_move = total_move - angular_move;
+        match self.sim.solver_parameters.position_correction {
+            PositionCorrection::Baumgarte => {}
+            PositionCorrection::SplitImpulse => {
+                for (rb, (linear, angular)) in self.sim.bodies.iter_mut().zip(
+                    self.pseudo_linear_velocities
+                        .iter()
+                        .zip(&self.pseudo_angular_velocities),
+                ) {
+                    rb.apply_displacement(linear * dt);
+                    rb.apply_rotation_vector(Quaternion::from_vec(angular) * dt);
+                }
+            }
+            PositionCorrection::NonLinearGaussSeidel => {
+                for _ in 0..self.sim.solver_parameters.position_iterations {
+                    self.solve_positions();
+                }
+            }
+        }
     }
+}
 
-    (linear_move, angular_move)
+/**
+ * Mass "seen" by an impulse along `direction` applied at the point: 1 / (J M^-1 J^T), null if nothing can move.
+ */
+fn effective_mass(
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+    rb1_2_point: &Vec3,
+    rb2_2_point: &Vec3,
+    direction: &Vec3,
+) -> Real {
+    let (_, _, rot_per_impulse_1) = rotation_per_unit_impulse(rb1, rb1_2_point, direction);
+    let (_, _, rot_per_impulse_2) = rotation_per_unit_impulse(rb2, rb2_2_point, direction);
+    let k =
+        rb1.inv_mass() + rb2.inv_mass() + dot(&(rot_per_impulse_1 + rot_per_impulse_2), direction);
+
+    if k > ZERO {
+        ONE / k
+    } else {
+        ZERO
+    }
 }
 
 /**
- * Source and most of the code :
- * http://www.r-5.org/files/books/computers/algo-list/realtime-3d/Ian_Millington-Game_Physics_Engine_Development-EN.pdf
+ * Applies `impulse` on rb2 and its opposite on rb1, nothing happens to a static body.
  */
-pub fn penetration_resolution_two_moving(
-    bodies: &mut Vec<RigidBody>,
+fn apply_impulse(
+    bodies: &mut [RigidBody],
     rb1_id: usize,
     rb2_id: usize,
-    p: &P3,
-    contact_infos: &ContactInformations,
+    rb1_2_point: &Vec3,
+    rb2_2_point: &Vec3,
+    impulse: &Vec3,
 ) {
-    let normal = contact_infos.normal_a_to_b;
-
-    let rel_contact_point_1 = p - bodies[rb1_id].center_of_mass();
-    let rel_contact_point_2 = p - bodies[rb2_id].center_of_mass();
-
-    // Computing the inertia, or what it takes to move a rigidbody by one unit
-    // in this case, its not impulsion, but movement
-    let (_, rotation_direction_1, angular_velocity_per_unit_impulse_1) =
-        rotation_per_unit_impulse(&bodies[rb1_id], &rel_contact_point_1, &normal);
-
-    // angular inertia per unit impulse in direction
-    let angular_inertia_1 = dot(&angular_velocity_per_unit_impulse_1, &normal);
-    let linear_inertia_1 = bodies[rb1_id].inv_mass();
-
-    let (_, rotation_direction_2, angular_velocity_per_unit_impulse_2) =
-        rotation_per_unit_impulse(&bodies[rb1_id], &rel_contact_point_2, &normal);
-
-    // angular inertia per unit impulse in direction
-    let angular_inertia_2 = dot(&angular_velocity_per_unit_impulse_2, &normal);
-    let linear_inertia_2 = bodies[rb2_id].inv_mass();
-
-    // Computing the movement
-    let penetration = contact_infos.penetration_distance;
-    let inv_total_inerta =
-        ONE / (angular_inertia_1 + linear_inertia_1 + angular_inertia_2 + linear_inertia_2);
-    let linear_move_1 = -penetration * linear_inertia_1 * inv_total_inerta;
-    let linear_move_2 = penetration * linear_inertia_2 * inv_total_inerta;
-    let angular_move_1 = -penetration * angular_inertia_1 * inv_total_inerta;
-    let angular_move_2 = penetration * angular_inertia_2 * inv_total_inerta;
-
-    let (linear_move_1, angular_move_1) =
-        limit_angular_displacement(linear_move_1, angular_move_1, &rel_contact_point_1);
-    let (linear_move_2, angular_move_2) =
-        limit_angular_displacement(linear_move_2, angular_move_2, &rel_contact_point_2);
-
-    // Applying the computed movement
-    let rotation_1 = rotation_direction_1 * angular_move_1 / angular_inertia_1;
-    let rotation_2 = rotation_direction_2 * angular_move_2 / angular_inertia_2;
-
-    bodies[rb1_id].apply_displacement(normal * linear_move_1);
-    bodies[rb2_id].apply_displacement(normal * linear_move_2);
-    bodies[rb1_id].apply_rotation_vector(Quaternion::from_vec(&rotation_1));
-    bodies[rb2_id].apply_rotation_vector(Quaternion::from_vec(&rotation_2));
+    bodies[rb1_id].apply_linear_impulse(-impulse);
+    bodies[rb1_id].apply_angular_impulse(cross(rb1_2_point, &(-impulse)));
+
+    bodies[rb2_id].apply_linear_impulse(*impulse);
+    bodies[rb2_id].apply_angular_impulse(cross(rb2_2_point, impulse));
 }
+
 /**
- * See velocity_resolution_one_moving() above
+ * Changes of linear and angular velocities of the body when `impulse` is applied at the point.
  */
-pub fn penetration_resolution_one_moving(
-    rb: &mut RigidBody,
-    p: &P3,
-    normal: Vec3,
-    penetration_distance: Real,
-) {
-    let rel_contact_point = p - rb.center_of_mass();
-    // Computing the inertia, or what it takes to move a rigidbody by one unit
-    // in this case, its not impulsion, but movement
-    let (_, rotation_direction, angular_velocity_per_unit_impulse) =
-        rotation_per_unit_impulse(&rb, &rel_contact_point, &normal);
+fn impulse_response(rb: &RigidBody, rb_2_point: &Vec3, impulse: &Vec3) -> (Vec3, Vec3) {
+    (
+        impulse * rb.inv_mass() * rb.translation_moving_axis,
+        rb.inv_inertia_tensor() * &cross(rb_2_point, impulse) * rb.rotation_moving_axis,
+    )
+}
+
+/**
+ * Return a triplet: the torque, the angular velocity and the velocity at the point per unit impulse along the normal
+ */
+pub fn rotation_per_unit_impulse(
+    rigidbody: &RigidBody,
+    point: &P3,
+    normal: &Vec3,
+) -> (Vec3, Vec3, Vec3) {
+    let torque_per_unit_impulse = cross(&point, &normal); // normal is the unit
+    let rotation_per_unit_impulse_v = rigidbody.inv_inertia_tensor() * &torque_per_unit_impulse;
+    let angular_velocity_per_unit_impulse = cross(&rotation_per_unit_impulse_v, &point);
+
+    (
+        torque_per_unit_impulse,
+        rotation_per_unit_impulse_v,
+        angular_velocity_per_unit_impulse,
+    )
+}
 
-    // angular inertia per unit impulse in direction
-    let angular_inertia = dot(&angular_velocity_per_unit_impulse, &normal);
+pub fn velocity_at_point(rigidbody: &RigidBody, relative_contact_point: &P3) -> Vec3 {
+    rigidbody.linear_velocity + cross(&rigidbody.angular_velocity, &relative_contact_point)
+}
+
+/**
+ * Separating velocity wanted along the normal after the collision.
+ * Under `consts::RESTITUTION_VELOCITY_THRESHOLD` the bodies don't bounce, so the resting contacts can settle.
+ * `approaching_velocity` is positive when the bodies move toward each other.
+ */
+pub fn restitution_velocity_bias(approaching_velocity: Real, restitution_coef: Real) -> Real {
+    if approaching_velocity > consts::RESTITUTION_VELOCITY_THRESHOLD {
+        restitution_coef * approaching_velocity
+    } else {
+        ZERO
+    }
+}
 
-    let linear_inertia = rb.inv_mass();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::collision::{CollisionObject, Material};
+    use crate::engine::shapes::Sphere;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn two_spheres(v1: Vec3, v2: Vec3, second_is_static: bool) -> SimulationWorld {
+        let mut sim = SimulationWorld::new(None);
+        let s1 = CollisionObject::new(Box::new(Sphere::new(ONE)));
+        let mut rb1 = RigidBody::new(Transform::identity(), ONE, false);
+        rb1.linear_velocity = v1;
+        sim.add_rigidbody(rb1, s1);
+
+        let s2 = CollisionObject::new(Box::new(Sphere::new(ONE)));
+        let mut rb2 = RigidBody::new(
+            Transform::translation(Vec3::new(1.9, ZERO, ZERO)),
+            ONE,
+            second_is_static,
+        );
+        rb2.linear_velocity = v2;
+        sim.add_rigidbody(rb2, s2);
+
+        sim.collision_world.step();
+        assert_eq!(sim.collision_world.contact_manifolds.len(), 1);
+        sim
+    }
 
-    // Computing the movement
-    let penetration = penetration_distance;
-    let inv_total_inerta = ONE / (angular_inertia + linear_inertia);
-    let linear_move = -penetration * linear_inertia * inv_total_inerta;
-    let angular_move = -penetration * angular_inertia * inv_total_inerta;
+    #[test]
+    fn head_on_keeps_momentum() {
+        let mut sim = two_spheres(
+            Vec3::new(4.0, ZERO, ZERO),
+            Vec3::new(-2.0, ZERO, ZERO),
+            false,
+        );
+        sim.solve_contact_manifolds();
+
+        let v1 = sim.rigidbody_ref(0).linear_velocity;
+        let v2 = sim.rigidbody_ref(1).linear_velocity;
+        let e = Material::default().restitution;
+        assert_approx_eq!(v1.x() + v2.x(), 2.0, 1e-4);
+        assert_approx_eq!(v2.x() - v1.x(), e * 6.0, 1e-4);
+    }
 
-    let (linear_move, angular_move) =
-        limit_angular_displacement(linear_move, angular_move, &rel_contact_point);
+    #[test]
+    fn separating_bodies_are_not_pulled_back() {
+        let mut sim = two_spheres(
+            Vec3::new(-1.0, ZERO, ZERO),
+            Vec3::new(1.0, ZERO, ZERO),
+            false,
+        );
+        sim.solve_contact_manifolds();
+
+        assert_eq!(sim.rigidbody_ref(0).linear_velocity.x(), -1.0);
+        assert_eq!(sim.rigidbody_ref(1).linear_velocity.x(), 1.0);
+        let cm = &sim.collision_world.contact_manifolds[0];
+        assert_eq!(cm.accumulated_impulses[0].normal, ZERO);
+    }
 
-    // Applying the computed movement
-    let rotation = rotation_direction * angular_move / angular_inertia;
+    #[test]
+    fn slow_contact_on_static_body_stops() {
+        let mut sim = two_spheres(Vec3::new(0.5, ZERO, ZERO), Vec3::zeros(), true);
+        sim.solve_contact_manifolds();
 
-    rb.apply_displacement(normal * linear_move);
-    rb.apply_rotation_vector(Quaternion::from_vec(&rotation));
+        // under the restitution threshold, no bounce
+        assert_approx_eq!(sim.rigidbody_ref(0).linear_velocity.x(), ZERO, 1e-5);
+        assert_eq!(sim.rigidbody_ref(1).linear_velocity, Vec3::zeros());
+        let cm = &sim.collision_world.contact_manifolds[0];
+        assert_approx_eq!(cm.accumulated_impulses[0].normal, 0.5, 1e-5);
+    }
 }
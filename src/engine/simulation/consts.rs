@@ -1,6 +1,8 @@
 use crate::math::math_essentials::*;
 
 pub const DELTA_TIME: Real = 1 as Real / 60 as Real;
+// steps at most per call to SimulationWorld::advance(), the late time is dropped
+pub const MAX_SUBSTEPS: usize = 8;
 pub const GRAVITY_ACCELERATION_CONST: Real = 9.81 as Real; // kg.m^-2
 pub const GRAVITY_DIRECTION: Vector3 = Vector3 {
     data: [0 as Real, -1 as Real, 0 as Real],
@@ -10,6 +12,14 @@ pub const GRAVITY_VECTOR: Vector3 = Vector3 {
 };
 pub const GRAVITY_INTEGRATED: Real = GRAVITY_ACCELERATION_CONST * DELTA_TIME;
 
-pub const ANGULAR_DAMPING: Real = 0.9;
+pub const VELOCITY_ITERATIONS: usize = 8;
 
-pub const ANGULAR_PENETRATION_DISPLACEMENT_FACTOR: Real = 0.2;
+// m.s^-1, slower contacts don't bounce
+pub const RESTITUTION_VELOCITY_THRESHOLD: Real = 1 as Real;
+
+pub const POSITION_ITERATIONS: usize = 3;
+pub const BAUMGARTE_FACTOR: Real = 0.2;
+// m, penetration left uncorrected
+pub const PENETRATION_SLOP: Real = 0.005;
+// m, maximum penetration corrected per step
+pub const MAX_PENETRATION_CORRECTION: Real = 0.2;
@@ -0,0 +1,50 @@
+use super::JointHandle;
+use crate::math::math_essentials::*;
+
+/**
+ * A contact between two non-sensor CollisionObjects.
+ * `max_impulse` is the biggest impulse applied on a contact point during the step,
+ * it is ZERO when the contact stopped.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ContactEvent {
+    pub id_collision_object_a: usize,
+    pub id_collision_object_b: usize,
+    pub normal_a_to_b: Vec3,
+    pub max_impulse: Real,
+}
+
+/**
+ * An overlap between a sensor and another CollisionObject.
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OverlapEvent {
+    pub id_collision_object_a: usize,
+    pub id_collision_object_b: usize,
+}
+
+/**
+ * A joint whose BreakThreshold has been exceeded, with the force and torque of the step.
+ * The joint is disabled.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointEvent {
+    pub handle: JointHandle,
+    pub force: Real,
+    pub torque: Real,
+}
+
+/**
+ * Emitted by SimulationWorld::discrete_step(), by comparing the pairs in contact
+ * with the ones of the previous step, and when a joint breaks.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SimulationEvent {
+    ContactStarted(ContactEvent),
+    ContactPersisted(ContactEvent),
+    ContactStopped(ContactEvent),
+    OverlapStarted(OverlapEvent),
+    OverlapPersisted(OverlapEvent),
+    OverlapStopped(OverlapEvent),
+    JointBroken(JointEvent),
+}
@@ -0,0 +1,61 @@
+use super::ForceGenerator;
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Spring of `stiffness` (N.m^-1) toward `rest_length` between a point of the body and a fixed anchor,
+ * with a damper of `damping` (N.s.m^-1) on the speed of the point along the spring.
+ */
+pub struct AnchoredSpring {
+    pub rb_id: usize,
+    // in the local frame of the body
+    pub local_point: P3,
+    // in world space
+    pub anchor: P3,
+    pub rest_length: Real,
+    pub stiffness: Real,
+    pub damping: Real,
+}
+
+impl AnchoredSpring {
+    /**
+     * `point` in world space, from the current position of the body
+     */
+    pub fn new(
+        rb: &RigidBody,
+        point: P3,
+        anchor: P3,
+        rest_length: Real,
+        stiffness: Real,
+        damping: Real,
+    ) -> AnchoredSpring {
+        AnchoredSpring {
+            rb_id: rb.id,
+            local_point: rb.transform.rotation.transposed() * (point - *rb.center_of_mass()),
+            anchor,
+            rest_length,
+            stiffness,
+            damping,
+        }
+    }
+}
+
+impl ForceGenerator for AnchoredSpring {
+    fn apply_forces(&mut self, bodies: &mut [RigidBody], _dt: Real) {
+        let rb = &mut bodies[self.rb_id];
+        let rb_2_point = rb.transform.rotation * self.local_point;
+        let point = rb.center_of_mass() + &rb_2_point;
+        let separation = self.anchor - point;
+        let length = magnitude(&separation);
+        if length == ZERO {
+            return;
+        }
+        let direction = separation / length;
+        let velocity = rb.linear_velocity + cross(&rb.angular_velocity, &rb_2_point);
+
+        let force = direction
+            * (self.stiffness * (length - self.rest_length)
+                - self.damping * dot(&velocity, &direction));
+        rb.apply_force(force, point);
+    }
+}
@@ -0,0 +1,41 @@
+use super::ForceGenerator;
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Drag growing with the square of the speed: the force is -linear_coefficient * |v| * v,
+ * and the torque -angular_coefficient * |w| * w.
+ */
+pub struct QuadraticDrag {
+    pub rb_ids: Vec<usize>,
+    pub linear_coefficient: Real,
+    pub angular_coefficient: Real,
+}
+
+impl QuadraticDrag {
+    pub fn new(
+        rb_ids: Vec<usize>,
+        linear_coefficient: Real,
+        angular_coefficient: Real,
+    ) -> QuadraticDrag {
+        QuadraticDrag {
+            rb_ids,
+            linear_coefficient,
+            angular_coefficient,
+        }
+    }
+}
+
+impl ForceGenerator for QuadraticDrag {
+    fn apply_forces(&mut self, bodies: &mut [RigidBody], _dt: Real) {
+        for id in &self.rb_ids {
+            let rb = &mut bodies[*id];
+            let force =
+                rb.linear_velocity * (-self.linear_coefficient * magnitude(&rb.linear_velocity));
+            let torque =
+                rb.angular_velocity * (-self.angular_coefficient * magnitude(&rb.angular_velocity));
+            rb.apply_central_force(force);
+            rb.apply_torque(torque);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::ForceGenerator;
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Pushes the bodies away from `center` once, at the next step.
+ * The impulse (N.s) on a body falls off linearly from `impulse` at the center to 0 at `radius`,
+ * it is applied as a force during the step.
+ */
+pub struct Explosion {
+    pub center: P3,
+    pub radius: Real,
+    pub impulse: Real,
+    detonated: bool,
+}
+
+impl Explosion {
+    pub fn new(center: P3, radius: Real, impulse: Real) -> Explosion {
+        Explosion {
+            center,
+            radius,
+            impulse,
+            detonated: false,
+        }
+    }
+
+    pub fn is_detonated(&self) -> bool {
+        self.detonated
+    }
+}
+
+impl ForceGenerator for Explosion {
+    fn apply_forces(&mut self, bodies: &mut [RigidBody], dt: Real) {
+        if self.detonated {
+            return;
+        }
+
+        for rb in bodies.iter_mut() {
+            let center_2_body = rb.center_of_mass() - &self.center;
+            let distance = magnitude(&center_2_body);
+            if distance == ZERO || distance >= self.radius {
+                continue;
+            }
+            let impulse = self.impulse * (ONE - distance / self.radius);
+            rb.apply_central_force(center_2_body * (impulse / (distance * dt)));
+        }
+    }
+
+    /**
+     * Spent at the end of the step it was applied in
+     */
+    fn end_step(&mut self) {
+        self.detonated = true;
+    }
+}
@@ -0,0 +1,37 @@
+pub mod anchored_spring;
+pub mod drag;
+pub mod explosion;
+pub mod wind;
+
+pub use anchored_spring::AnchoredSpring;
+pub use drag::QuadraticDrag;
+pub use explosion::Explosion;
+pub use wind::WindVolume;
+
+use super::RigidBody;
+use crate::math::math_essentials::*;
+use downcast_rs::DowncastSync;
+
+/**
+ * Index of the generator in the SimulationWorld, given by SimulationWorld::add_force_generator()
+ */
+pub type ForceGeneratorHandle = usize;
+
+/**
+ * Applies forces on the bodies before the velocities are integrated,
+ * with RigidBody::apply_force() and RigidBody::apply_torque().
+ * The static and kinematic bodies ignore the forces.
+ * apply_forces() only depends on the state of the bodies and doesn't change the generator:
+ * a generator changes once per step, in end_step() (an Explosion is spent there).
+ */
+pub trait ForceGenerator: DowncastSync {
+    /**
+     * Can be called several times per step by the Integrator, at intermediate states of the bodies
+     */
+    fn apply_forces(&mut self, bodies: &mut [RigidBody], dt: Real);
+    /**
+     * Called once at the end of each step
+     */
+    fn end_step(&mut self) {}
+}
+impl_downcast!(sync ForceGenerator);
@@ -0,0 +1,39 @@
+use super::ForceGenerator;
+use crate::engine::simulation::RigidBody;
+use crate::geometry::aabb::Aabb;
+use crate::math::math_essentials::*;
+
+/**
+ * Wind blowing at `velocity` inside `volume`: the bodies whose center of mass is inside
+ * get a quadratic drag of `coefficient` on their velocity relative to the wind.
+ */
+pub struct WindVolume {
+    pub volume: Aabb,
+    pub velocity: Vec3,
+    pub coefficient: Real,
+}
+
+impl WindVolume {
+    pub fn new(volume: Aabb, velocity: Vec3, coefficient: Real) -> WindVolume {
+        WindVolume {
+            volume,
+            velocity,
+            coefficient,
+        }
+    }
+}
+
+impl ForceGenerator for WindVolume {
+    fn apply_forces(&mut self, bodies: &mut [RigidBody], _dt: Real) {
+        for rb in bodies.iter_mut() {
+            let center = *rb.center_of_mass();
+            if !self.volume.contains(&Aabb::new(center, center)) {
+                continue;
+            }
+            let relative_velocity = self.velocity - rb.linear_velocity;
+            rb.apply_central_force(
+                relative_velocity * (self.coefficient * magnitude(&relative_velocity)),
+            );
+        }
+    }
+}
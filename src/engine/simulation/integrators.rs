@@ -0,0 +1,269 @@
+use super::{RigidBody, SimulationWorld};
+use crate::math::{math_essentials::*, Mat3, Quaternion};
+use std::collections::HashSet;
+
+/**
+ * How the velocities and the positions of the bodies are integrated over a step.
+ * The forces are evaluated once per step with SemiImplicitEuler, twice with VelocityVerlet
+ * and four times with RungeKutta4, at intermediate states of the bodies.
+ * The bodies touched by the solver (contacts, joint rows) are always moved with their solved velocities,
+ * the other integrators only change the trajectory of the free bodies.
+ * The static and kinematic bodies are not integrated.
+ */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    #[default]
+    SemiImplicitEuler,
+    VelocityVerlet,
+    RungeKutta4,
+}
+
+/**
+ * Transform and velocities of a body
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct BodyState {
+    pub transform: Transform,
+    pub linear_velocity: Vec3,
+    pub angular_velocity: Vec3,
+}
+
+impl BodyState {
+    pub fn of(rb: &RigidBody) -> BodyState {
+        BodyState {
+            transform: rb.transform,
+            linear_velocity: rb.linear_velocity,
+            angular_velocity: rb.angular_velocity,
+        }
+    }
+
+    pub fn apply(&self, rb: &mut RigidBody) {
+        rb.set_transform(self.transform);
+        rb.linear_velocity = self.linear_velocity;
+        rb.angular_velocity = self.angular_velocity;
+    }
+
+    /**
+     * Moved at the velocities `(linear, angular)`,
+     * its velocities changed by the accelerations `(linear, angular)`, during `dt`
+     */
+    fn advanced(
+        &self,
+        velocities: &(Vec3, Vec3),
+        accelerations: &(Vec3, Vec3),
+        dt: Real,
+    ) -> BodyState {
+        BodyState {
+            transform: Transform {
+                rotation: rotated(&self.transform.rotation, &velocities.1, dt),
+                translation: self.transform.translation + velocities.0 * dt,
+            },
+            linear_velocity: self.linear_velocity + accelerations.0 * dt,
+            angular_velocity: self.angular_velocity + accelerations.1 * dt,
+        }
+    }
+}
+
+/**
+ * Same quaternion derivative as RigidBody::apply_rotation_vector()
+ */
+fn rotated(rotation: &Mat3, angular_velocity: &Vec3, dt: Real) -> Mat3 {
+    let q = Quaternion::from_mat3(rotation)
+        .quaternion_derivative(Quaternion::from_vec(angular_velocity) * dt);
+    (q / q.magnitude()).to_mat3()
+}
+
+/**
+ * Weighted sum of the pairs of vectors
+ */
+fn weighted_sum(pairs: &[(Vec3, Vec3)], weights: &[Real]) -> (Vec3, Vec3) {
+    pairs.iter().zip(weights).fold(
+        (Vec3::zeros(), Vec3::zeros()),
+        |(sum_0, sum_1), (pair, weight)| (sum_0 + pair.0 * *weight, sum_1 + pair.1 * *weight),
+    )
+}
+
+/**
+ * Moves every dynamic body to its state, evaluates the forces there
+ * and returns the accelerations of every body (null if not dynamic).
+ */
+fn accelerations(sim: &mut SimulationWorld, states: &[Option<BodyState>]) -> Vec<(Vec3, Vec3)> {
+    for (rb, state) in sim.bodies.iter_mut().zip(states) {
+        if let Some(state) = state {
+            state.apply(rb);
+        }
+    }
+    sim.apply_forces();
+
+    let gravity = sim.gravity;
+    sim.bodies
+        .iter_mut()
+        .map(|rb| {
+            if rb.is_dynamic() {
+                rb.apply_gravity(gravity);
+            }
+            rb.take_accelerations()
+        })
+        .collect()
+}
+
+fn initial_states(sim: &SimulationWorld) -> Vec<Option<BodyState>> {
+    sim.bodies
+        .iter()
+        .map(|rb| rb.is_dynamic().then(|| BodyState::of(rb)))
+        .collect()
+}
+
+fn velocities(states: &[Option<BodyState>]) -> Vec<(Vec3, Vec3)> {
+    states
+        .iter()
+        .map(|state| {
+            state.map_or((Vec3::zeros(), Vec3::zeros()), |state| {
+                (state.linear_velocity, state.angular_velocity)
+            })
+        })
+        .collect()
+}
+
+fn advanced(
+    states: &[Option<BodyState>],
+    velocities: &[(Vec3, Vec3)],
+    accelerations: &[(Vec3, Vec3)],
+    dt: Real,
+) -> Vec<Option<BodyState>> {
+    states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| state.map(|state| state.advanced(&velocities[i], &accelerations[i], dt)))
+        .collect()
+}
+
+/**
+ * x1 = x0 + v0 dt + a0 dt²/2, v1 = v0 + (a0 + a1) dt/2,
+ * the forces depending on the velocity see the velocity of the half step at x1.
+ * See finish() for the result.
+ */
+pub fn velocity_verlet(sim: &mut SimulationWorld) -> Vec<Option<BodyState>> {
+    let dt = sim.time_step;
+    let initial_states = initial_states(sim);
+
+    let a0 = accelerations(sim, &initial_states);
+    let half_step = advanced(&initial_states, &velocities(&initial_states), &a0, dt / TWO);
+    let moved = advanced(&initial_states, &velocities(&half_step), &a0, dt);
+    let a1 = accelerations(sim, &half_step_at(&moved, &half_step));
+    let a: Vec<(Vec3, Vec3)> = a0
+        .iter()
+        .zip(&a1)
+        .map(|(a0, a1)| weighted_sum(&[*a0, *a1], &[0.5, 0.5]))
+        .collect();
+    let final_states = advanced(&initial_states, &velocities(&half_step), &a, dt);
+
+    finish(sim, &initial_states, final_states)
+}
+
+/**
+ * Classic Runge-Kutta of order 4 on the positions and the velocities.
+ * See finish() for the result.
+ */
+pub fn runge_kutta_4(sim: &mut SimulationWorld) -> Vec<Option<BodyState>> {
+    let dt = sim.time_step;
+    let initial_states = initial_states(sim);
+
+    let v1 = velocities(&initial_states);
+    let a1 = accelerations(sim, &initial_states);
+    let s2 = advanced(&initial_states, &v1, &a1, dt / TWO);
+    let v2 = velocities(&s2);
+    let a2 = accelerations(sim, &s2);
+    let s3 = advanced(&initial_states, &v2, &a2, dt / TWO);
+    let v3 = velocities(&s3);
+    let a3 = accelerations(sim, &s3);
+    let s4 = advanced(&initial_states, &v3, &a3, dt);
+    let v4 = velocities(&s4);
+    let a4 = accelerations(sim, &s4);
+
+    let weights = [ONE / 6.0, ONE / 3.0, ONE / 3.0, ONE / 6.0];
+    let v: Vec<(Vec3, Vec3)> = (0..v1.len())
+        .map(|i| weighted_sum(&[v1[i], v2[i], v3[i], v4[i]], &weights))
+        .collect();
+    let a: Vec<(Vec3, Vec3)> = (0..a1.len())
+        .map(|i| weighted_sum(&[a1[i], a2[i], a3[i], a4[i]], &weights))
+        .collect();
+    let final_states = advanced(&initial_states, &v, &a, dt);
+
+    finish(sim, &initial_states, final_states)
+}
+
+/**
+ * The dynamic bodies go back to the beginning of the step, with the (damped) velocities of the end of the step,
+ * like after RigidBody::integrate_velocities(), and the kinematic velocities are updated.
+ * The returned states (None if not dynamic) are where the dynamic bodies end if nothing touches them.
+ */
+fn finish(
+    sim: &mut SimulationWorld,
+    initial_states: &[Option<BodyState>],
+    final_states: Vec<Option<BodyState>>,
+) -> Vec<Option<BodyState>> {
+    let dt = sim.time_step;
+    for ((rb, initial_state), final_state) in
+        sim.bodies.iter_mut().zip(initial_states).zip(&final_states)
+    {
+        if let (Some(initial_state), Some(final_state)) = (initial_state, final_state) {
+            initial_state.apply(rb);
+            rb.linear_velocity = final_state.linear_velocity;
+            rb.angular_velocity = final_state.angular_velocity;
+            rb.apply_damping(dt);
+        } else if rb.is_kinematic {
+            rb.update_kinematic_velocities(dt);
+        }
+    }
+
+    final_states
+        .into_iter()
+        .zip(&sim.bodies)
+        .map(|(state, rb)| {
+            state.map(|state| BodyState {
+                linear_velocity: rb.linear_velocity,
+                angular_velocity: rb.angular_velocity,
+                ..state
+            })
+        })
+        .collect()
+}
+
+/**
+ * The transforms of `moved` with the velocities of `half_step`
+ */
+fn half_step_at(
+    moved: &[Option<BodyState>],
+    half_step: &[Option<BodyState>],
+) -> Vec<Option<BodyState>> {
+    moved
+        .iter()
+        .zip(half_step)
+        .map(|(moved, half_step)| match (moved, half_step) {
+            (Some(moved), Some(half_step)) => Some(BodyState {
+                transform: moved.transform,
+                ..*half_step
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/**
+ * Moves the free bodies, the ones not in `constrained_bodies` (see ContactSolver::constrained_bodies()),
+ * to their integrated state
+ */
+pub fn move_free_bodies(
+    bodies: &mut [RigidBody],
+    states: &[Option<BodyState>],
+    constrained_bodies: &HashSet<usize>,
+) {
+    for (rb, state) in bodies.iter_mut().zip(states) {
+        if let Some(state) = state {
+            if !constrained_bodies.contains(&rb.id) {
+                rb.set_transform(state.transform);
+            }
+        }
+    }
+}
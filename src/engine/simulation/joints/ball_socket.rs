@@ -0,0 +1,75 @@
+use super::{local_point, update_rows, ConstraintRow, Joint};
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Spherical joint: the anchors of the two bodies stay at the same point, the rotations are free.
+ */
+pub struct BallSocketJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    rows: Vec<ConstraintRow>,
+}
+
+impl BallSocketJoint {
+    /**
+     * `anchor` in world space, from the current positions of the bodies
+     */
+    pub fn new(rb1: &RigidBody, rb2: Option<&RigidBody>, anchor: P3) -> BallSocketJoint {
+        BallSocketJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor),
+            local_anchor_2: local_point(rb2, &anchor),
+            rows: Vec::new(),
+        }
+    }
+}
+
+/**
+ * Three linear rows along the world axes, the bias brings the anchors back together.
+ */
+pub fn point_rows(
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+    local_anchor_1: &P3,
+    local_anchor_2: &P3,
+    bias_factor: Real,
+) -> [ConstraintRow; 3] {
+    let rb1_2_anchor = rb1.transform.rotation * *local_anchor_1;
+    let rb2_2_anchor = rb2.transform.rotation * *local_anchor_2;
+    let error = (rb2.center_of_mass() + &rb2_2_anchor) - (rb1.center_of_mass() + &rb1_2_anchor);
+
+    [Directions::right(), Directions::up(), Directions::forward()].map(|axis| {
+        ConstraintRow::linear(&rb1_2_anchor, &rb2_2_anchor, &axis)
+            .with_bias(dot(&error, &axis) * bias_factor)
+    })
+}
+
+impl Joint for BallSocketJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let rows = point_rows(
+            rb1,
+            rb2,
+            &self.local_anchor_1,
+            &self.local_anchor_2,
+            baumgarte_factor / dt,
+        );
+        update_rows(&mut self.rows, rows.to_vec(), rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+}
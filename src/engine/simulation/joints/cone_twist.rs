@@ -0,0 +1,219 @@
+use super::ball_socket::point_rows;
+use super::{
+    axis_frame, local_point, update_rows, ConstraintRow, Joint, JointLimits, LimitSoftness,
+};
+use crate::engine::simulation::RigidBody;
+use crate::math::{math_essentials::*, Mat3, Quaternion};
+
+/**
+ * Ball and socket joint for the limbs: the rotation of rb2 relative to rb1 is decomposed
+ * into a twist around the twist axis (the bone) then a swing of this axis.
+ * The swing angle is limited by a cone, the twist angle by its own limits,
+ * both null when the joint is created.
+ * A limit is hard without softness, else it gives under load.
+ */
+pub struct ConeTwistJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    // the first column is the twist axis
+    pub local_frame_1: Mat3,
+    pub local_frame_2: Mat3,
+    // half angle of the cone, in radians
+    pub swing_span: Option<Real>,
+    pub twist_limits: Option<JointLimits>,
+    pub swing_softness: Option<LimitSoftness>,
+    pub twist_softness: Option<LimitSoftness>,
+    swing_angle: Real,
+    twist_angle: Real,
+    rows: Vec<ConstraintRow>,
+}
+
+impl ConeTwistJoint {
+    /**
+     * `anchor` and `twist_axis` in world space, from the current positions of the bodies
+     */
+    pub fn new(
+        rb1: &RigidBody,
+        rb2: Option<&RigidBody>,
+        anchor: P3,
+        twist_axis: Vec3,
+    ) -> ConeTwistJoint {
+        let frame = axis_frame(&twist_axis);
+        let local_frame = |rb: Option<&RigidBody>| match rb {
+            Some(rb) => rb.transform.rotation.transposed() * frame,
+            None => frame,
+        };
+
+        ConeTwistJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor),
+            local_anchor_2: local_point(rb2, &anchor),
+            local_frame_1: local_frame(Some(rb1)),
+            local_frame_2: local_frame(rb2),
+            swing_span: None,
+            twist_limits: None,
+            swing_softness: None,
+            twist_softness: None,
+            swing_angle: ZERO,
+            twist_angle: ZERO,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn with_swing_limit(mut self, span: Real) -> ConeTwistJoint {
+        self.swing_span = Some(span);
+        self
+    }
+
+    pub fn with_twist_limits(mut self, lower: Real, upper: Real) -> ConeTwistJoint {
+        self.twist_limits = Some(JointLimits::new(lower, upper));
+        self
+    }
+
+    pub fn with_swing_softness(mut self, stiffness: Real, damping: Real) -> ConeTwistJoint {
+        self.swing_softness = Some(LimitSoftness::new(stiffness, damping));
+        self
+    }
+
+    pub fn with_twist_softness(mut self, stiffness: Real, damping: Real) -> ConeTwistJoint {
+        self.twist_softness = Some(LimitSoftness::new(stiffness, damping));
+        self
+    }
+
+    /**
+     * In radians, in [0, PI], at the end of the last step
+     */
+    pub fn swing_angle(&self) -> Real {
+        self.swing_angle
+    }
+
+    /**
+     * In radians, in ]-PI, PI], at the end of the last step
+     */
+    pub fn twist_angle(&self) -> Real {
+        self.twist_angle
+    }
+
+    /**
+     * The swing axis, null without swing, and the twist axis in world space,
+     * then the swing and twist angles
+     */
+    fn measure(&self, rb1: &RigidBody, rb2: &RigidBody) -> (Vec3, Vec3, Real, Real) {
+        let rotation_1 = rb1.transform.rotation * self.local_frame_1;
+        let rotation_2 = rb2.transform.rotation * self.local_frame_2;
+        // in the frame of rb1, w is positive
+        let q = Quaternion::from_mat3(&(rotation_1.transposed() * rotation_2));
+
+        // rotation = swing * twist, the twist keeps the part of q around the twist axis
+        let twist_magnitude = q.w.hypot(q.x);
+        let twist = if twist_magnitude > ZERO {
+            Quaternion {
+                w: q.w / twist_magnitude,
+                x: q.x / twist_magnitude,
+                y: ZERO,
+                z: ZERO,
+            }
+        } else {
+            // swing of PI, the twist is undefined
+            Quaternion {
+                w: ONE,
+                x: ZERO,
+                y: ZERO,
+                z: ZERO,
+            }
+        };
+        let swing = q * twist.conjugate();
+
+        let swing_sin = swing.y.hypot(swing.z);
+        let swing_axis = if swing_sin > Real::EPSILON {
+            rotation_1 * Vec3::new(ZERO, swing.y / swing_sin, swing.z / swing_sin)
+        } else {
+            Vec3::zeros()
+        };
+        let swing_angle = TWO * swing_sin.atan2(swing.w);
+        let twist_angle = TWO * twist.x.atan2(twist.w);
+
+        (swing_axis, rotation_2.col(0), swing_angle, twist_angle)
+    }
+}
+
+fn limit_row(
+    row: ConstraintRow,
+    limits: &JointLimits,
+    softness: &Option<LimitSoftness>,
+    position: Real,
+    dt: Real,
+    baumgarte_factor: Real,
+) -> ConstraintRow {
+    match softness {
+        Some(softness) => limits.soft_limit_row(row, position, dt, baumgarte_factor, softness),
+        None => limits.limit_row(row, position, dt, baumgarte_factor),
+    }
+}
+
+impl Joint for ConeTwistJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    /**
+     * 3 rows for the anchors, then the swing limit and the twist limit, inactive when not set
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let (swing_axis, twist_axis, swing_angle, twist_angle) = self.measure(rb1, rb2);
+
+        let mut rows = point_rows(
+            rb1,
+            rb2,
+            &self.local_anchor_1,
+            &self.local_anchor_2,
+            baumgarte_factor / dt,
+        )
+        .to_vec();
+        let row = ConstraintRow::angular(&swing_axis);
+        rows.push(match self.swing_span {
+            // no swing, no direction to limit
+            Some(span) if swing_axis != Vec3::zeros() => limit_row(
+                row,
+                &JointLimits::new(-span, span),
+                &self.swing_softness,
+                swing_angle,
+                dt,
+                baumgarte_factor,
+            ),
+            _ => row.with_bounds(ZERO, ZERO),
+        });
+        let row = ConstraintRow::angular(&twist_axis);
+        rows.push(match self.twist_limits {
+            Some(limits) => limit_row(
+                row,
+                &limits,
+                &self.twist_softness,
+                twist_angle,
+                dt,
+                baumgarte_factor,
+            ),
+            None => row.with_bounds(ZERO, ZERO),
+        });
+
+        update_rows(&mut self.rows, rows, rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (_, _, swing_angle, twist_angle) = self.measure(rb1, rb2);
+        self.swing_angle = swing_angle;
+        self.twist_angle = twist_angle;
+    }
+}
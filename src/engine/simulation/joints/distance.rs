@@ -0,0 +1,111 @@
+use super::{local_point, update_rows, ConstraintRow, Joint, JointLimits};
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Keeps the distance between the anchors in [min_length, max_length], a rod when they are equal.
+ * The rotations are free.
+ */
+pub struct DistanceJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    pub min_length: Real,
+    pub max_length: Real,
+    length: Real,
+    rows: Vec<ConstraintRow>,
+}
+
+impl DistanceJoint {
+    /**
+     * Anchors in world space, from the current positions of the bodies
+     */
+    pub fn new(
+        rb1: &RigidBody,
+        rb2: Option<&RigidBody>,
+        anchor_1: P3,
+        anchor_2: P3,
+        min_length: Real,
+        max_length: Real,
+    ) -> DistanceJoint {
+        debug_assert!(min_length <= max_length);
+
+        DistanceJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor_1),
+            local_anchor_2: local_point(rb2, &anchor_2),
+            min_length,
+            max_length,
+            length: magnitude(&(anchor_2 - anchor_1)),
+            rows: Vec::new(),
+        }
+    }
+
+    /**
+     * Distance between the anchors at the end of the last step
+     */
+    pub fn length(&self) -> Real {
+        self.length
+    }
+}
+
+/**
+ * The arms of the anchors and the vector between the anchors
+ */
+pub fn anchors_separation(
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+    local_anchor_1: &P3,
+    local_anchor_2: &P3,
+) -> (Vec3, Vec3, Vec3) {
+    let rb1_2_anchor = rb1.transform.rotation * *local_anchor_1;
+    let rb2_2_anchor = rb2.transform.rotation * *local_anchor_2;
+    let separation =
+        (rb2.center_of_mass() + &rb2_2_anchor) - (rb1.center_of_mass() + &rb1_2_anchor);
+
+    (rb1_2_anchor, rb2_2_anchor, separation)
+}
+
+impl Joint for DistanceJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    /**
+     * One row along the anchors, free in both directions for a rod,
+     * otherwise only active at the limits.
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let (rb1_2_anchor, rb2_2_anchor, separation) =
+            anchors_separation(rb1, rb2, &self.local_anchor_1, &self.local_anchor_2);
+        let length = magnitude(&separation);
+        let direction = if length > ZERO {
+            separation / length
+        } else {
+            Directions::up()
+        };
+
+        let row = ConstraintRow::linear(&rb1_2_anchor, &rb2_2_anchor, &direction);
+        let limits = JointLimits::new(self.min_length, self.max_length);
+        let row = limits.limit_row(row, length, dt, baumgarte_factor);
+
+        update_rows(&mut self.rows, vec![row], rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (_, _, separation) =
+            anchors_separation(rb1, rb2, &self.local_anchor_1, &self.local_anchor_2);
+        self.length = magnitude(&separation);
+    }
+}
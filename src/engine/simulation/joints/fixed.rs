@@ -0,0 +1,71 @@
+use super::ball_socket::point_rows;
+use super::prismatic::rotation_lock_rows;
+use super::{local_point, update_rows, ConstraintRow, Joint};
+use crate::engine::simulation::RigidBody;
+use crate::math::{math_essentials::*, Mat3};
+
+/**
+ * Weld joint: the anchors stay together and the bodies keep their relative orientation.
+ */
+pub struct FixedJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    // rotation of rb2 in the frame of rb1
+    local_rotation: Mat3,
+    rows: Vec<ConstraintRow>,
+}
+
+impl FixedJoint {
+    /**
+     * `anchor` in world space, the bodies are glued in their current positions
+     */
+    pub fn new(rb1: &RigidBody, rb2: Option<&RigidBody>, anchor: P3) -> FixedJoint {
+        let rotation_2 = rb2.map_or(Mat3::identity(), |rb| rb.transform.rotation);
+
+        FixedJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor),
+            local_anchor_2: local_point(rb2, &anchor),
+            local_rotation: rb1.transform.rotation.transposed() * rotation_2,
+            rows: Vec::new(),
+        }
+    }
+}
+
+impl Joint for FixedJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let bias_factor = baumgarte_factor / dt;
+        let mut rows = point_rows(
+            rb1,
+            rb2,
+            &self.local_anchor_1,
+            &self.local_anchor_2,
+            bias_factor,
+        )
+        .to_vec();
+        rows.extend(rotation_lock_rows(
+            rb1,
+            rb2,
+            &self.local_rotation,
+            bias_factor,
+        ));
+
+        update_rows(&mut self.rows, rows, rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+}
@@ -0,0 +1,163 @@
+use super::ball_socket::point_rows;
+use super::{
+    local_point, local_vector, update_rows, ConstraintRow, Joint, JointLimits, JointMotor,
+};
+use crate::engine::simulation::RigidBody;
+use crate::geometry::helper::perp;
+use crate::math::math_essentials::*;
+
+/**
+ * Revolute joint: the anchors stay together and the bodies only rotate around the hinge axis.
+ * The angle is the rotation of rb2 relative to rb1 around the axis, null when the joint is created.
+ * Attached to the world (no rb2), it is the rotation of rb1 itself.
+ */
+pub struct HingeJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    pub local_axis_1: Vec3,
+    pub local_axis_2: Vec3,
+    pub limits: Option<JointLimits>,
+    pub motor: Option<JointMotor>,
+    // perpendicular to the axis, the angle is measured between them
+    local_reference_1: Vec3,
+    local_reference_2: Vec3,
+    angle: Real,
+    angular_speed: Real,
+    rows: Vec<ConstraintRow>,
+}
+
+impl HingeJoint {
+    /**
+     * `anchor` and `axis` in world space, from the current positions of the bodies
+     */
+    pub fn new(rb1: &RigidBody, rb2: Option<&RigidBody>, anchor: P3, axis: Vec3) -> HingeJoint {
+        let axis = normalized(axis);
+        let (mut reference, _) = perp(&axis);
+        normalize(&mut reference);
+
+        HingeJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor),
+            local_anchor_2: local_point(rb2, &anchor),
+            local_axis_1: local_vector(Some(rb1), &axis),
+            local_axis_2: local_vector(rb2, &axis),
+            limits: None,
+            motor: None,
+            local_reference_1: local_vector(Some(rb1), &reference),
+            local_reference_2: local_vector(rb2, &reference),
+            angle: ZERO,
+            angular_speed: ZERO,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn with_limits(mut self, lower: Real, upper: Real) -> HingeJoint {
+        self.limits = Some(JointLimits::new(lower, upper));
+        self
+    }
+
+    pub fn with_motor(mut self, target_speed: Real, max_torque: Real) -> HingeJoint {
+        self.motor = Some(JointMotor::new(target_speed, max_torque));
+        self
+    }
+
+    /**
+     * In radians, in ]-PI, PI], at the end of the last step
+     */
+    pub fn angle(&self) -> Real {
+        self.angle
+    }
+
+    /**
+     * In rad.s^-1, at the end of the last step
+     */
+    pub fn angular_speed(&self) -> Real {
+        self.angular_speed
+    }
+
+    /**
+     * Returns the axis of the measures, the angle and the angular speed
+     */
+    fn measure(&self, rb1: &RigidBody, rb2: &RigidBody) -> (Vec3, Real, Real) {
+        let axis = rb1.transform.rotation * self.local_axis_1;
+        // the world is rb2, the rotation of rb1 relative to the world is the opposite one
+        let axis = match self.rb2_id {
+            Some(_) => axis,
+            None => -axis,
+        };
+        let reference_1 = rb1.transform.rotation * self.local_reference_1;
+        let reference_2 = rb2.transform.rotation * self.local_reference_2;
+        let angle =
+            dot(&cross(&reference_1, &reference_2), &axis).atan2(dot(&reference_1, &reference_2));
+        let angular_speed = dot(&(rb2.angular_velocity - rb1.angular_velocity), &axis);
+
+        (axis, angle, angular_speed)
+    }
+}
+
+impl Joint for HingeJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    /**
+     * 3 rows for the anchors, 2 rows for the rotations around the axes perpendicular to the hinge,
+     * then the limit and the motor, inactive when not set.
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let bias_factor = baumgarte_factor / dt;
+        let (axis, angle, _) = self.measure(rb1, rb2);
+        // rotation taking the axis of rb1 onto the one of rb2
+        let error = cross(
+            &(rb1.transform.rotation * self.local_axis_1),
+            &(rb2.transform.rotation * self.local_axis_2),
+        );
+        let (mut perpendicular_1, mut perpendicular_2) = perp(&axis);
+        normalize(&mut perpendicular_1);
+        normalize(&mut perpendicular_2);
+
+        let mut rows = point_rows(
+            rb1,
+            rb2,
+            &self.local_anchor_1,
+            &self.local_anchor_2,
+            bias_factor,
+        )
+        .to_vec();
+        for perpendicular in [perpendicular_1, perpendicular_2] {
+            rows.push(
+                ConstraintRow::angular(&perpendicular)
+                    .with_bias(dot(&error, &perpendicular) * bias_factor),
+            );
+        }
+        let row = ConstraintRow::angular(&axis);
+        rows.push(match self.limits {
+            Some(limits) => limits.limit_row(row, angle, dt, baumgarte_factor),
+            None => row.with_bounds(ZERO, ZERO),
+        });
+        rows.push(match self.motor {
+            Some(motor) => motor.motor_row(row, dt),
+            None => row.with_bounds(ZERO, ZERO),
+        });
+
+        update_rows(&mut self.rows, rows, rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (_, angle, angular_speed) = self.measure(rb1, rb2);
+        self.angle = angle;
+        self.angular_speed = angular_speed;
+    }
+}
@@ -0,0 +1,405 @@
+pub mod ball_socket;
+pub mod cone_twist;
+pub mod distance;
+pub mod fixed;
+pub mod hinge;
+pub mod prismatic;
+pub mod six_dof;
+pub mod spring;
+
+pub use ball_socket::BallSocketJoint;
+pub use cone_twist::ConeTwistJoint;
+pub use distance::DistanceJoint;
+pub use fixed::FixedJoint;
+pub use hinge::HingeJoint;
+pub use prismatic::PrismaticJoint;
+pub use six_dof::{AxisDrive, AxisMotion, SixDofJoint};
+pub use spring::SpringJoint;
+
+use super::RigidBody;
+use crate::geometry::helper::perp;
+use crate::math::{math_essentials::*, Mat3};
+use downcast_rs::DowncastSync;
+
+/**
+ * Index of the joint in the SimulationWorld, given by SimulationWorld::add_joint()
+ */
+pub type JointHandle = usize;
+
+/**
+ * A constraint between two rigidbodies, or a rigidbody and the world.
+ * It is made of ConstraintRow, solved with the contacts in the same iterations of the ContactSolver.
+ * The drift of a joint is corrected by a Baumgarte bias on its rows, whatever the PositionCorrection.
+ */
+pub trait Joint: DowncastSync {
+    /**
+     * The second rigidbody is the world when None
+     */
+    fn rigidbody_ids(&self) -> (usize, Option<usize>);
+    /**
+     * Builds the rows from the positions at the beginning of the step,
+     * `rb2` is a static body at the origin for the world.
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real);
+    fn rows(&self) -> &[ConstraintRow];
+    fn rows_mut(&mut self) -> &mut [ConstraintRow];
+    /**
+     * Called before the velocities are integrated, for the joints acting with forces
+     */
+    fn apply_forces(&mut self, _rb1: &mut RigidBody, _rb2: &mut RigidBody) {}
+    /**
+     * Called once the positions are integrated, lets the joint measure its new state
+     */
+    fn update_measures(&mut self, _rb1: &RigidBody, _rb2: &RigidBody) {}
+
+    /**
+     * Linear and angular impulses applied on rb2 during the last step,
+     * the rows without linear part are the angular ones.
+     */
+    fn reaction_impulses(&self) -> (Vec3, Vec3) {
+        let mut linear = Vec3::zeros();
+        let mut angular = Vec3::zeros();
+        for row in self.rows() {
+            if row.linear == Vec3::zeros() {
+                angular += row.angular_2 * row.impulse;
+            } else {
+                linear += row.linear * row.impulse;
+            }
+        }
+
+        (linear, angular)
+    }
+}
+impl_downcast!(sync Joint);
+
+/**
+ * Force (N) and torque (N.m) over which a joint breaks, INFINITY to ignore one of them
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BreakThreshold {
+    pub max_force: Real,
+    pub max_torque: Real,
+}
+
+impl BreakThreshold {
+    pub fn new(max_force: Real, max_torque: Real) -> BreakThreshold {
+        BreakThreshold {
+            max_force,
+            max_torque,
+        }
+    }
+
+    pub fn is_exceeded(&self, force: Real, torque: Real) -> bool {
+        force > self.max_force || torque > self.max_torque
+    }
+}
+
+/**
+ * Range allowed for the position of a joint along a degree of freedom, angle in radians or distance
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointLimits {
+    pub lower: Real,
+    pub upper: Real,
+}
+
+impl JointLimits {
+    pub fn new(lower: Real, upper: Real) -> JointLimits {
+        debug_assert!(lower <= upper);
+        JointLimits { lower, upper }
+    }
+
+    /**
+     * Row keeping `position` in the limits, it acts on the nearest limit.
+     * Inside the limits, it only stops the velocities that would cross the limit during the step,
+     * outside, the Baumgarte bias brings the position back.
+     */
+    pub fn limit_row(
+        &self,
+        row: ConstraintRow,
+        position: Real,
+        dt: Real,
+        baumgarte_factor: Real,
+    ) -> ConstraintRow {
+        if self.lower == self.upper {
+            return row.with_bias((position - self.lower) * baumgarte_factor / dt);
+        }
+
+        if position - self.lower < self.upper - position {
+            let error = position - self.lower;
+            let bias = if error > ZERO {
+                error / dt
+            } else {
+                error * baumgarte_factor / dt
+            };
+            row.with_bias(bias).with_bounds(ZERO, Real::INFINITY)
+        } else {
+            let error = position - self.upper;
+            let bias = if error < ZERO {
+                error / dt
+            } else {
+                error * baumgarte_factor / dt
+            };
+            row.with_bias(bias).with_bounds(-Real::INFINITY, ZERO)
+        }
+    }
+
+    /**
+     * Same as limit_row(), but outside the limits the position is brought back by a spring and a damper
+     * instead of the Baumgarte bias, as a soft row.
+     */
+    pub fn soft_limit_row(
+        &self,
+        row: ConstraintRow,
+        position: Real,
+        dt: Real,
+        baumgarte_factor: Real,
+        softness: &LimitSoftness,
+    ) -> ConstraintRow {
+        let row = self.limit_row(row, position, dt, baumgarte_factor);
+        let error = if position < self.lower {
+            position - self.lower
+        } else if position > self.upper {
+            position - self.upper
+        } else {
+            return row;
+        };
+        let d = softness.damping + dt * softness.stiffness;
+        if d <= ZERO {
+            return row;
+        }
+
+        row.with_softness(ONE / (dt * d))
+            .with_bias(error * softness.stiffness / d)
+    }
+}
+
+/**
+ * Spring (N.m^-1 or N.m.rad^-1) and damper making a limit give under load
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LimitSoftness {
+    pub stiffness: Real,
+    pub damping: Real,
+}
+
+impl LimitSoftness {
+    pub fn new(stiffness: Real, damping: Real) -> LimitSoftness {
+        LimitSoftness { stiffness, damping }
+    }
+}
+
+/**
+ * Drives a degree of freedom at `target_speed` (rad.s^-1 or m.s^-1),
+ * with at most `max_force` (a torque for a rotation).
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointMotor {
+    pub target_speed: Real,
+    pub max_force: Real,
+}
+
+impl JointMotor {
+    pub fn new(target_speed: Real, max_force: Real) -> JointMotor {
+        JointMotor {
+            target_speed,
+            max_force,
+        }
+    }
+
+    pub fn motor_row(&self, row: ConstraintRow, dt: Real) -> ConstraintRow {
+        let max_impulse = self.max_force * dt;
+        row.with_bias(-self.target_speed)
+            .with_bounds(-max_impulse, max_impulse)
+    }
+}
+
+/**
+ * One degree of freedom removed by a joint, its jacobian is [-linear, -angular_1, linear, angular_2].
+ * The accumulated impulse is clamped in [min_impulse, max_impulse], a row with null bounds is inactive.
+ * The impulse is kept from a step to the next to warm start the joint.
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct ConstraintRow {
+    pub linear: Vec3,
+    pub angular_1: Vec3,
+    pub angular_2: Vec3,
+    // the velocity wanted along the row is -bias
+    pub bias: Real,
+    // 0 for a rigid row
+    pub softness: Real,
+    pub min_impulse: Real,
+    pub max_impulse: Real,
+    pub impulse: Real,
+    // effective mass 1 / (J M^-1 J^T)
+    mass: Real,
+}
+
+impl ConstraintRow {
+    fn new(linear: Vec3, angular_1: Vec3, angular_2: Vec3) -> ConstraintRow {
+        ConstraintRow {
+            linear,
+            angular_1,
+            angular_2,
+            bias: ZERO,
+            softness: ZERO,
+            min_impulse: -Real::INFINITY,
+            max_impulse: Real::INFINITY,
+            impulse: ZERO,
+            mass: ZERO,
+        }
+    }
+
+    /**
+     * Relative velocity of the anchors along `direction`,
+     * `rb1_2_anchor` and `rb2_2_anchor` go from the center of mass of each body to its anchor.
+     */
+    pub fn linear(rb1_2_anchor: &Vec3, rb2_2_anchor: &Vec3, direction: &Vec3) -> ConstraintRow {
+        ConstraintRow::new(
+            *direction,
+            cross(rb1_2_anchor, direction),
+            cross(rb2_2_anchor, direction),
+        )
+    }
+
+    /**
+     * Relative angular velocity around `axis`
+     */
+    pub fn angular(axis: &Vec3) -> ConstraintRow {
+        ConstraintRow::new(Vec3::zeros(), *axis, *axis)
+    }
+
+    pub fn with_bias(mut self, bias: Real) -> ConstraintRow {
+        self.bias = bias;
+        self
+    }
+
+    pub fn with_bounds(mut self, min_impulse: Real, max_impulse: Real) -> ConstraintRow {
+        self.min_impulse = min_impulse;
+        self.max_impulse = max_impulse;
+        self
+    }
+
+    pub fn with_softness(mut self, softness: Real) -> ConstraintRow {
+        self.softness = softness;
+        self
+    }
+
+    pub fn velocity(&self, rb1: &RigidBody, rb2: &RigidBody) -> Real {
+        dot(&self.linear, &(rb2.linear_velocity - rb1.linear_velocity))
+            + dot(&self.angular_2, &rb2.angular_velocity)
+            - dot(&self.angular_1, &rb1.angular_velocity)
+    }
+
+    pub fn compute_mass(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let k = (rb1.inv_mass() + rb2.inv_mass()) * squared_magnitude(&self.linear)
+            + dot(
+                &(rb1.inv_inertia_tensor() * &self.angular_1),
+                &self.angular_1,
+            )
+            + dot(
+                &(rb2.inv_inertia_tensor() * &self.angular_2),
+                &self.angular_2,
+            )
+            + self.softness;
+
+        self.mass = if k > ZERO { ONE / k } else { ZERO };
+    }
+
+    pub fn apply_impulse(&self, rb1: &mut RigidBody, rb2: &mut RigidBody, impulse: Real) {
+        rb1.apply_linear_impulse(-self.linear * impulse);
+        rb1.apply_angular_impulse(-self.angular_1 * impulse);
+        rb2.apply_linear_impulse(self.linear * impulse);
+        rb2.apply_angular_impulse(self.angular_2 * impulse);
+    }
+
+    // helper::clamp() refuses null bounds
+    fn clamp_impulse(&self, impulse: Real) -> Real {
+        helper::max(helper::min(impulse, self.max_impulse), self.min_impulse)
+    }
+
+    pub fn warm_start(&self, rb1: &mut RigidBody, rb2: &mut RigidBody) {
+        self.apply_impulse(rb1, rb2, self.impulse);
+    }
+
+    pub fn solve(&mut self, rb1: &mut RigidBody, rb2: &mut RigidBody) {
+        let lambda =
+            -self.mass * (self.velocity(rb1, rb2) + self.bias + self.softness * self.impulse);
+        let previous_impulse = self.impulse;
+        self.impulse = self.clamp_impulse(previous_impulse + lambda);
+        self.apply_impulse(rb1, rb2, self.impulse - previous_impulse);
+    }
+}
+
+/**
+ * Replaces the rows of a joint by the ones of this step, computes their masses
+ * and keeps the impulses of the previous step (by index) for the warm start.
+ */
+pub fn update_rows(
+    rows: &mut Vec<ConstraintRow>,
+    new_rows: Vec<ConstraintRow>,
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+) {
+    let previous_impulses: Vec<Real> = rows.iter().map(|row| row.impulse).collect();
+    *rows = new_rows;
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.compute_mass(rb1, rb2);
+        if let Some(impulse) = previous_impulses.get(i) {
+            row.impulse = row.clamp_impulse(*impulse);
+        }
+    }
+}
+
+/**
+ * The two bodies of a joint, `world` stands for the missing second one.
+ */
+pub fn joint_bodies<'a>(
+    bodies: &'a mut [RigidBody],
+    world: &'a mut RigidBody,
+    rb1_id: usize,
+    rb2_id: Option<usize>,
+) -> (&'a mut RigidBody, &'a mut RigidBody) {
+    match rb2_id {
+        None => (&mut bodies[rb1_id], world),
+        Some(rb2_id) if rb1_id < rb2_id => {
+            let (first, second) = bodies.split_at_mut(rb2_id);
+            (&mut first[rb1_id], &mut second[0])
+        }
+        Some(rb2_id) => {
+            let (first, second) = bodies.split_at_mut(rb1_id);
+            (&mut second[0], &mut first[rb2_id])
+        }
+    }
+}
+
+/**
+ * `point` in the local frame of the body, the world frame for the world
+ */
+pub fn local_point(rb: Option<&RigidBody>, point: &P3) -> P3 {
+    match rb {
+        Some(rb) => rb.transform.rotation.transposed() * (point - rb.center_of_mass()),
+        None => *point,
+    }
+}
+
+/**
+ * `vector` in the local frame of the body, the world frame for the world
+ */
+pub fn local_vector(rb: Option<&RigidBody>, vector: &Vec3) -> Vec3 {
+    match rb {
+        Some(rb) => rb.transform.rotation.transposed() * *vector,
+        None => *vector,
+    }
+}
+
+/**
+ * Orthonormal frame whose first column is `axis`, normalized
+ */
+pub fn axis_frame(axis: &Vec3) -> Mat3 {
+    let axis = normalized(*axis);
+    let (mut reference, _) = perp(&axis);
+    normalize(&mut reference);
+
+    Mat3::from_array([axis.data, reference.data, cross(&axis, &reference).data]).transposed()
+}
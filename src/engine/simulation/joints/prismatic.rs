@@ -0,0 +1,173 @@
+use super::{
+    local_point, local_vector, update_rows, ConstraintRow, Joint, JointLimits, JointMotor,
+};
+use crate::engine::simulation::RigidBody;
+use crate::geometry::helper::perp;
+use crate::math::{math_essentials::*, Mat3, Quaternion};
+
+/**
+ * Slider joint: the bodies keep their relative orientation and rb2 only moves along the axis,
+ * which follows rb1. The translation is the distance between the anchors along the axis,
+ * null when the joint is created. Attached to the world (no rb2), it is the displacement of rb1 itself.
+ */
+pub struct PrismaticJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    pub local_axis_1: Vec3,
+    pub limits: Option<JointLimits>,
+    pub motor: Option<JointMotor>,
+    // rotation of rb2 in the frame of rb1
+    local_rotation: Mat3,
+    translation: Real,
+    speed: Real,
+    rows: Vec<ConstraintRow>,
+}
+
+impl PrismaticJoint {
+    /**
+     * `anchor` and `axis` in world space, from the current positions of the bodies
+     */
+    pub fn new(rb1: &RigidBody, rb2: Option<&RigidBody>, anchor: P3, axis: Vec3) -> PrismaticJoint {
+        let rotation_2 = rb2.map_or(Mat3::identity(), |rb| rb.transform.rotation);
+
+        PrismaticJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor),
+            local_anchor_2: local_point(rb2, &anchor),
+            local_axis_1: local_vector(Some(rb1), &normalized(axis)),
+            limits: None,
+            motor: None,
+            local_rotation: rb1.transform.rotation.transposed() * rotation_2,
+            translation: ZERO,
+            speed: ZERO,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn with_limits(mut self, lower: Real, upper: Real) -> PrismaticJoint {
+        self.limits = Some(JointLimits::new(lower, upper));
+        self
+    }
+
+    pub fn with_motor(mut self, target_speed: Real, max_force: Real) -> PrismaticJoint {
+        self.motor = Some(JointMotor::new(target_speed, max_force));
+        self
+    }
+
+    /**
+     * At the end of the last step
+     */
+    pub fn translation(&self) -> Real {
+        self.translation
+    }
+
+    /**
+     * In m.s^-1, at the end of the last step
+     */
+    pub fn speed(&self) -> Real {
+        self.speed
+    }
+
+    /**
+     * The row along the axis, the translation and the separation of the anchors
+     */
+    fn measure(&self, rb1: &RigidBody, rb2: &RigidBody) -> (ConstraintRow, Real, Vec3) {
+        let axis = rb1.transform.rotation * self.local_axis_1;
+        // the world is rb2, the displacement of rb1 relative to the world is the opposite one
+        let axis = match self.rb2_id {
+            Some(_) => axis,
+            None => -axis,
+        };
+        let rb1_2_anchor = rb1.transform.rotation * self.local_anchor_1;
+        let rb2_2_anchor = rb2.transform.rotation * self.local_anchor_2;
+        let separation =
+            (rb2.center_of_mass() + &rb2_2_anchor) - (rb1.center_of_mass() + &rb1_2_anchor);
+        // rb1 holds the constraint at the anchor of rb2
+        let row = ConstraintRow::linear(&(rb1_2_anchor + separation), &rb2_2_anchor, &axis);
+
+        (row, dot(&separation, &axis), separation)
+    }
+}
+
+/**
+ * Three angular rows along the world axes, the bias brings rb2 back
+ * to its rotation `local_rotation` in the frame of rb1.
+ */
+pub fn rotation_lock_rows(
+    rb1: &RigidBody,
+    rb2: &RigidBody,
+    local_rotation: &Mat3,
+    bias_factor: Real,
+) -> [ConstraintRow; 3] {
+    let error = rotation_error(rb1, rb2, local_rotation);
+
+    [Directions::right(), Directions::up(), Directions::forward()]
+        .map(|axis| ConstraintRow::angular(&axis).with_bias(dot(&error, &axis) * bias_factor))
+}
+
+/**
+ * Rotation vector, in world space, from the wanted rotation of rb2 to its current one
+ */
+pub fn rotation_error(rb1: &RigidBody, rb2: &RigidBody, local_rotation: &Mat3) -> Vec3 {
+    let wanted_rotation = rb1.transform.rotation * *local_rotation;
+    // w is positive, the shortest rotation
+    let q = Quaternion::from_mat3(&(rb2.transform.rotation * wanted_rotation.transposed()));
+
+    Vec3::new(q.x, q.y, q.z) * TWO
+}
+
+impl Joint for PrismaticJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    /**
+     * 3 rows locking the rotations, 2 rows keeping rb2 on the axis, then the limit and the motor,
+     * inactive when not set.
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let bias_factor = baumgarte_factor / dt;
+        let (row, translation, separation) = self.measure(rb1, rb2);
+        let (mut perpendicular_1, mut perpendicular_2) = perp(&row.linear);
+        normalize(&mut perpendicular_1);
+        normalize(&mut perpendicular_2);
+        let rb2_2_anchor = rb2.transform.rotation * self.local_anchor_2;
+        let rb1_2_anchor = rb2.center_of_mass() + &rb2_2_anchor - *rb1.center_of_mass();
+
+        let mut rows = rotation_lock_rows(rb1, rb2, &self.local_rotation, bias_factor).to_vec();
+        for perpendicular in [perpendicular_1, perpendicular_2] {
+            rows.push(
+                ConstraintRow::linear(&rb1_2_anchor, &rb2_2_anchor, &perpendicular)
+                    .with_bias(dot(&separation, &perpendicular) * bias_factor),
+            );
+        }
+        rows.push(match self.limits {
+            Some(limits) => limits.limit_row(row, translation, dt, baumgarte_factor),
+            None => row.with_bounds(ZERO, ZERO),
+        });
+        rows.push(match self.motor {
+            Some(motor) => motor.motor_row(row, dt),
+            None => row.with_bounds(ZERO, ZERO),
+        });
+
+        update_rows(&mut self.rows, rows, rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (row, translation, _) = self.measure(rb1, rb2);
+        self.translation = translation;
+        self.speed = row.velocity(rb1, rb2);
+    }
+}
@@ -0,0 +1,288 @@
+use super::{update_rows, ConstraintRow, Joint, JointLimits};
+use crate::engine::simulation::RigidBody;
+use crate::math::{math_essentials::*, rotation_vector};
+
+/**
+ * What a degree of freedom of a SixDofJoint allows
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AxisMotion {
+    Locked,
+    Limited(JointLimits),
+    Free,
+}
+
+/**
+ * Spring toward `target_position` and damper toward `target_speed` on a degree of freedom,
+ * with at most `max_force` (a torque for a rotation).
+ * With a null stiffness, it is a velocity drive.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AxisDrive {
+    pub target_position: Real,
+    pub target_speed: Real,
+    pub stiffness: Real,
+    pub damping: Real,
+    pub max_force: Real,
+}
+
+impl AxisDrive {
+    pub fn new(
+        target_position: Real,
+        stiffness: Real,
+        damping: Real,
+        max_force: Real,
+    ) -> AxisDrive {
+        AxisDrive {
+            target_position,
+            target_speed: ZERO,
+            stiffness,
+            damping,
+            max_force,
+        }
+    }
+
+    /**
+     * Soft row: the spring and the damper are turned into a softness and a bias,
+     * so the drive stays stable whatever its stiffness.
+     *
+     * Source : Erin Catto, Soft Constraints
+     * https://box2d.org/files/ErinCatto_SoftConstraints_GDC2011.pdf
+     */
+    pub fn drive_row(&self, row: ConstraintRow, position: Real, dt: Real) -> ConstraintRow {
+        let d = self.damping + dt * self.stiffness;
+        if d <= ZERO {
+            return row.with_bounds(ZERO, ZERO);
+        }
+        let softness = ONE / (dt * d);
+        let bias_factor = self.stiffness / d;
+        let max_impulse = self.max_force * dt;
+
+        row.with_softness(softness)
+            .with_bias((position - self.target_position) * bias_factor - self.target_speed)
+            .with_bounds(-max_impulse, max_impulse)
+    }
+}
+
+/**
+ * Configurable joint between a frame on each body: along and around each axis of the frame of rb1,
+ * the frame of rb2 is locked, limited or free, and can be driven.
+ * The linear positions are the coordinates of the origin of the frame of rb2 in the frame of rb1.
+ * The angular positions are the components of the rotation vector from the frame of rb1 to the one of rb2,
+ * exact for a rotation around one axis.
+ * Attached to the world (no rb2), the positions are the ones of the frame of rb1 itself.
+ */
+pub struct SixDofJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_frame_1: Transform,
+    pub local_frame_2: Transform,
+    pub linear_motions: [AxisMotion; 3],
+    pub angular_motions: [AxisMotion; 3],
+    pub linear_drives: [Option<AxisDrive>; 3],
+    pub angular_drives: [Option<AxisDrive>; 3],
+    linear_positions: [Real; 3],
+    angular_positions: [Real; 3],
+    rows: Vec<ConstraintRow>,
+}
+
+impl SixDofJoint {
+    /**
+     * Every axis is locked
+     */
+    pub fn new(
+        rb1_id: usize,
+        rb2_id: Option<usize>,
+        local_frame_1: Transform,
+        local_frame_2: Transform,
+    ) -> SixDofJoint {
+        SixDofJoint {
+            rb1_id,
+            rb2_id,
+            local_frame_1,
+            local_frame_2,
+            linear_motions: [AxisMotion::Locked; 3],
+            angular_motions: [AxisMotion::Locked; 3],
+            linear_drives: [None; 3],
+            angular_drives: [None; 3],
+            linear_positions: [ZERO; 3],
+            angular_positions: [ZERO; 3],
+            rows: Vec::new(),
+        }
+    }
+
+    /**
+     * Both frames at `frame`, in world space, from the current positions of the bodies
+     */
+    pub fn from_world_frame(
+        rb1: &RigidBody,
+        rb2: Option<&RigidBody>,
+        frame: Transform,
+    ) -> SixDofJoint {
+        SixDofJoint::new(
+            rb1.id,
+            rb2.map(|rb| rb.id),
+            local_frame(Some(rb1), &frame),
+            local_frame(rb2, &frame),
+        )
+    }
+
+    pub fn with_linear_motion(mut self, axis: usize, motion: AxisMotion) -> SixDofJoint {
+        self.linear_motions[axis] = motion;
+        self
+    }
+
+    pub fn with_angular_motion(mut self, axis: usize, motion: AxisMotion) -> SixDofJoint {
+        self.angular_motions[axis] = motion;
+        self
+    }
+
+    pub fn with_linear_drive(mut self, axis: usize, drive: AxisDrive) -> SixDofJoint {
+        self.linear_drives[axis] = Some(drive);
+        self
+    }
+
+    pub fn with_angular_drive(mut self, axis: usize, drive: AxisDrive) -> SixDofJoint {
+        self.angular_drives[axis] = Some(drive);
+        self
+    }
+
+    /**
+     * At the end of the last step
+     */
+    pub fn linear_position(&self, axis: usize) -> Real {
+        self.linear_positions[axis]
+    }
+
+    /**
+     * In radians, at the end of the last step
+     */
+    pub fn angular_position(&self, axis: usize) -> Real {
+        self.angular_positions[axis]
+    }
+
+    /**
+     * The axes of the frame of rb1, the arms of the origins of the frames,
+     * the separation of the origins and the rotation vector between the frames, in world space
+     */
+    fn measure(&self, rb1: &RigidBody, rb2: &RigidBody) -> ([Vec3; 3], Vec3, Vec3, Vec3, Vec3) {
+        let rotation_1 = rb1.transform.rotation * self.local_frame_1.rotation;
+        let rotation_2 = rb2.transform.rotation * self.local_frame_2.rotation;
+        let rb1_2_origin = rb1.transform.rotation * self.local_frame_1.translation;
+        let rb2_2_origin = rb2.transform.rotation * self.local_frame_2.translation;
+        let separation =
+            (rb2.center_of_mass() + &rb2_2_origin) - (rb1.center_of_mass() + &rb1_2_origin);
+        let relative_rotation = rotation_vector(&(rotation_2 * rotation_1.transposed()));
+        // the world is rb2, the frame of rb1 relative to the world is measured along the opposite axes
+        let sign = match self.rb2_id {
+            Some(_) => ONE,
+            None => -ONE,
+        };
+        let axes = [0, 1, 2].map(|i| rotation_1.col(i) * sign);
+
+        (
+            axes,
+            rb1_2_origin,
+            rb2_2_origin,
+            separation,
+            relative_rotation,
+        )
+    }
+}
+
+/**
+ * `frame`, in world space, in the local frame of the body
+ */
+fn local_frame(rb: Option<&RigidBody>, frame: &Transform) -> Transform {
+    match rb {
+        Some(rb) => {
+            let inv_rotation = rb.transform.rotation.transposed();
+            Transform {
+                rotation: inv_rotation * frame.rotation,
+                translation: inv_rotation * (frame.translation - *rb.center_of_mass()),
+            }
+        }
+        None => *frame,
+    }
+}
+
+/**
+ * The row of a degree of freedom for its motion, then its drive
+ */
+fn axis_rows(
+    row: ConstraintRow,
+    position: Real,
+    motion: &AxisMotion,
+    drive: &Option<AxisDrive>,
+    dt: Real,
+    baumgarte_factor: Real,
+) -> [ConstraintRow; 2] {
+    let motion_row = match motion {
+        AxisMotion::Locked => row.with_bias(position * baumgarte_factor / dt),
+        AxisMotion::Limited(limits) => limits.limit_row(row, position, dt, baumgarte_factor),
+        AxisMotion::Free => row.with_bounds(ZERO, ZERO),
+    };
+    let drive_row = match drive {
+        Some(drive) => drive.drive_row(row, position, dt),
+        None => row.with_bounds(ZERO, ZERO),
+    };
+
+    [motion_row, drive_row]
+}
+
+impl Joint for SixDofJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    /**
+     * Two rows per axis, the linear ones then the angular ones
+     */
+    fn pre_compute(&mut self, rb1: &RigidBody, rb2: &RigidBody, dt: Real, baumgarte_factor: Real) {
+        let (axes, rb1_2_origin, rb2_2_origin, separation, relative_rotation) =
+            self.measure(rb1, rb2);
+        // rb1 holds the constraint at the origin of the frame of rb2
+        let rb1_2_origin = rb1_2_origin + separation;
+
+        let mut rows = Vec::with_capacity(12);
+        for (i, axis) in axes.iter().enumerate() {
+            rows.extend(axis_rows(
+                ConstraintRow::linear(&rb1_2_origin, &rb2_2_origin, axis),
+                dot(&separation, axis),
+                &self.linear_motions[i],
+                &self.linear_drives[i],
+                dt,
+                baumgarte_factor,
+            ));
+        }
+        for (i, axis) in axes.iter().enumerate() {
+            rows.extend(axis_rows(
+                ConstraintRow::angular(axis),
+                dot(&relative_rotation, axis),
+                &self.angular_motions[i],
+                &self.angular_drives[i],
+                dt,
+                baumgarte_factor,
+            ));
+        }
+
+        update_rows(&mut self.rows, rows, rb1, rb2);
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &self.rows
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut self.rows
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (axes, _, _, separation, relative_rotation) = self.measure(rb1, rb2);
+        for (i, axis) in axes.iter().enumerate() {
+            self.linear_positions[i] = dot(&separation, axis);
+            self.angular_positions[i] = dot(&relative_rotation, axis);
+        }
+    }
+}
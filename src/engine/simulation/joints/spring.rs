@@ -0,0 +1,107 @@
+use super::distance::anchors_separation;
+use super::{local_point, ConstraintRow, Joint};
+use crate::engine::simulation::RigidBody;
+use crate::math::math_essentials::*;
+
+/**
+ * Soft link between the anchors: a spring of `stiffness` (N.m^-1) toward `rest_length`
+ * and a damper of `damping` (N.s.m^-1) on the relative speed along the anchors.
+ * It has no row, its force is applied on the bodies before the velocities are integrated.
+ */
+pub struct SpringJoint {
+    pub rb1_id: usize,
+    pub rb2_id: Option<usize>,
+    // in the local frame of each body, the world frame for the world
+    pub local_anchor_1: P3,
+    pub local_anchor_2: P3,
+    pub rest_length: Real,
+    pub stiffness: Real,
+    pub damping: Real,
+    length: Real,
+}
+
+impl SpringJoint {
+    /**
+     * Anchors in world space, from the current positions of the bodies
+     */
+    pub fn new(
+        rb1: &RigidBody,
+        rb2: Option<&RigidBody>,
+        anchor_1: P3,
+        anchor_2: P3,
+        rest_length: Real,
+        stiffness: Real,
+        damping: Real,
+    ) -> SpringJoint {
+        SpringJoint {
+            rb1_id: rb1.id,
+            rb2_id: rb2.map(|rb| rb.id),
+            local_anchor_1: local_point(Some(rb1), &anchor_1),
+            local_anchor_2: local_point(rb2, &anchor_2),
+            rest_length,
+            stiffness,
+            damping,
+            length: magnitude(&(anchor_2 - anchor_1)),
+        }
+    }
+
+    /**
+     * Distance between the anchors at the end of the last step
+     */
+    pub fn length(&self) -> Real {
+        self.length
+    }
+}
+
+impl Joint for SpringJoint {
+    fn rigidbody_ids(&self) -> (usize, Option<usize>) {
+        (self.rb1_id, self.rb2_id)
+    }
+
+    fn pre_compute(
+        &mut self,
+        _rb1: &RigidBody,
+        _rb2: &RigidBody,
+        _dt: Real,
+        _baumgarte_factor: Real,
+    ) {
+    }
+
+    fn rows(&self) -> &[ConstraintRow] {
+        &[]
+    }
+
+    fn rows_mut(&mut self) -> &mut [ConstraintRow] {
+        &mut []
+    }
+
+    /**
+     * Pulls the anchors together when the spring is longer than its rest length, pushes them apart otherwise
+     */
+    fn apply_forces(&mut self, rb1: &mut RigidBody, rb2: &mut RigidBody) {
+        let (rb1_2_anchor, rb2_2_anchor, separation) =
+            anchors_separation(rb1, rb2, &self.local_anchor_1, &self.local_anchor_2);
+        let length = magnitude(&separation);
+        if length == ZERO {
+            return;
+        }
+        let direction = separation / length;
+        let relative_velocity = rb2.linear_velocity + cross(&rb2.angular_velocity, &rb2_2_anchor)
+            - rb1.linear_velocity
+            - cross(&rb1.angular_velocity, &rb1_2_anchor);
+
+        let force = direction
+            * (self.stiffness * (length - self.rest_length)
+                + self.damping * dot(&relative_velocity, &direction));
+        let anchor_1 = rb1.center_of_mass() + &rb1_2_anchor;
+        let anchor_2 = rb2.center_of_mass() + &rb2_2_anchor;
+        rb1.apply_force(force, anchor_1);
+        rb2.apply_force(-force, anchor_2);
+    }
+
+    fn update_measures(&mut self, rb1: &RigidBody, rb2: &RigidBody) {
+        let (_, _, separation) =
+            anchors_separation(rb1, rb2, &self.local_anchor_1, &self.local_anchor_2);
+        self.length = magnitude(&separation);
+    }
+}
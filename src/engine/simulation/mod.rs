@@ -1,19 +1,61 @@
 pub mod collision_solver;
 pub mod consts;
+pub mod events;
+pub mod force_generators;
+pub mod integrators;
+pub mod joints;
+pub mod ragdoll;
 pub mod rigid_body;
 
+pub use collision_solver::{ContactSolver, PositionCorrection, SolverParameters};
+pub use events::{ContactEvent, JointEvent, OverlapEvent, SimulationEvent};
+pub use force_generators::{
+    AnchoredSpring, Explosion, ForceGenerator, ForceGeneratorHandle, QuadraticDrag, WindVolume,
+};
+pub use integrators::Integrator;
+pub use joints::{
+    AxisDrive, AxisMotion, BallSocketJoint, BreakThreshold, ConeTwistJoint, DistanceJoint,
+    FixedJoint, HingeJoint, Joint, JointHandle, JointLimits, JointMotor, LimitSoftness,
+    PrismaticJoint, SixDofJoint, SpringJoint,
+};
+pub use ragdoll::{Bone, BoneJoint, Ragdoll};
 pub use rigid_body::RigidBody;
 
 use crate::engine::collision::{CollisionObject, CollisionWorld};
-use crate::engine::contact_algorithms::{ContactInformations, ContactManifold};
+use crate::engine::contact_algorithms::ContactManifold;
 use crate::math::math_essentials::*;
+use joints::joint_bodies;
+use std::collections::{HashMap, HashSet};
+
+/**
+ * A joint and its state in the world
+ */
+struct JointSlot {
+    joint: Box<dyn Joint>,
+    enabled: bool,
+    break_threshold: Option<BreakThreshold>,
+}
 
 pub struct SimulationWorld {
     bodies: Vec<RigidBody>,
+    joints: Vec<JointSlot>,
+    force_generators: Vec<Box<dyn ForceGenerator>>,
+    // the second body of the joints attached to the world
+    world_body: RigidBody,
     time_step: Real,
+    // time not simulated yet by advance(), less than a step
+    accumulator: Real,
+    pub max_substeps: usize,
+    gravity: Vec3,
     pub collision_world: CollisionWorld,
+    pub solver_parameters: SolverParameters,
+    pub integrator: Integrator,
+    // pairs of collision objects in contact at the previous step, with their last normal
+    previous_contacts: HashMap<(usize, usize), Vec3>,
+    previous_overlaps: HashSet<(usize, usize)>,
+    events: Vec<SimulationEvent>,
 }
-    
+
 impl SimulationWorld {
     /**
      * `time_step` is 1/60sec (consts::DELTA_TIME) if None
@@ -25,16 +67,41 @@ impl SimulationWorld {
         }
         SimulationWorld {
             bodies: Vec::new(),
+            joints: Vec::new(),
+            force_generators: Vec::new(),
+            world_body: RigidBody::new(Transform::identity(), ZERO, true),
             time_step: dt,
+            accumulator: ZERO,
+            max_substeps: consts::MAX_SUBSTEPS,
+            gravity: consts::GRAVITY_VECTOR,
             collision_world: CollisionWorld::new(),
+            solver_parameters: SolverParameters::default(),
+            integrator: Integrator::default(),
+            previous_contacts: HashMap::new(),
+            previous_overlaps: HashSet::new(),
+            events: Vec::new(),
         }
     }
 
+    /**
+     * Acceleration applied to every body, consts::GRAVITY_VECTOR by default
+     */
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vec3) {
+        self.gravity = gravity;
+    }
+
     pub fn add_rigidbody(&mut self, mut rb: RigidBody, co: CollisionObject) -> usize {
         let i = self.bodies.len();
         rb.id = i;
         let co_id = self.collision_world.add_collision_object(co);
         rb.link_and_set_collision_object(self.collision_world.collision_object_mut(co_id).unwrap());
+        // the shape has been moved onto the rigidbody, the broadphase needs to know it
+        self.collision_world
+            .update_transform_collision_object(co_id, rb.transform);
         self.bodies.push(rb);
 
         i
@@ -48,173 +115,368 @@ impl SimulationWorld {
         &mut self.bodies[id]
     }
 
-    pub fn pre_start_set_appart(&mut self) {
-        self.collision_world.step();
+    /**
+     * The joint is solved with the contacts from the next step
+     */
+    pub fn add_joint(&mut self, joint: Box<dyn Joint>) -> JointHandle {
+        let (rb1_id, rb2_id) = joint.rigidbody_ids();
+        debug_assert!(rb1_id < self.bodies.len());
+        debug_assert!(rb2_id.is_none_or(|id| id < self.bodies.len() && id != rb1_id));
+        self.joints.push(JointSlot {
+            joint,
+            enabled: true,
+            break_threshold: None,
+        });
 
-        self.set_appart();
+        self.joints.len() - 1
+    }
 
-        self.collision_world.contact_manifolds.clear();
+    pub fn joint_ref(&self, handle: JointHandle) -> &dyn Joint {
+        self.joints[handle].joint.as_ref()
     }
 
-    pub fn solve_contact_manifolds(&mut self) {
-        println!("============================ solve_contact_manifolds =================================");
+    pub fn joint_mut(&mut self, handle: JointHandle) -> &mut dyn Joint {
+        self.joints[handle].joint.as_mut()
+    }
 
-        println!(
-            "solve_contact_manifolds {:?}",
-            self.collision_world.contact_manifolds.len()
-        );
-        for cm in &self.collision_world.contact_manifolds {
-            let rb1_id = self
-                .collision_world
-                .collision_object_ref(cm.id_collision_object_a)
-                .unwrap()
-                .rigidbody_id
-                .unwrap();
-            let rb2_id = self
-                .collision_world
-                .collision_object_ref(cm.id_collision_object_b)
-                .unwrap()
-                .rigidbody_id
-                .unwrap();
-
-            println!(
-                "Points de collisions ({:?}) : ",
-                cm.contact_infos.points.len(),
-            );
-            for p in &cm.contact_infos.points {
-                println!("       {:?}", p);
-            }
-            println!(
-                "normal{:?} distance{:?}",
-                cm.contact_infos.normal_a_to_b, cm.contact_infos.penetration_distance
-            );
-            match (self.bodies[rb1_id].is_static, self.bodies[rb2_id].is_static) {
-                (false, false) => {
-                    println!("both mving");
-
-                    for p in &cm.contact_infos.points {
-                        collision_solver::velocity_resolution_two_moving(
-                            &mut self.bodies,
-                            rb1_id,
-                            rb2_id,
-                            &p,
-                            &cm.contact_infos,
-                        );
-                    }
-                }
-                (false, true) => {
-                    println!("First mving");
-                    println!(
-                        "static={:?} moving={:?}",
-                        self.bodies[rb2_id].transform.translation,
-                        self.bodies[rb1_id].transform.translation,
-                    );
+    /**
+     * The generator applies its forces from the next step
+     */
+    pub fn add_force_generator(
+        &mut self,
+        generator: Box<dyn ForceGenerator>,
+    ) -> ForceGeneratorHandle {
+        self.force_generators.push(generator);
 
-                    for p in &cm.contact_infos.points {
-                        collision_solver::velocity_resolution_one_moving(
-                            &mut self.bodies[rb1_id],
-                            &p,
-                            cm.contact_infos.normal_a_to_b,
-                            cm.contact_infos.penetration_distance,
-                        );
-                    }
-                }
-                (true, false) => {
-                    println!("second mving");
-                    println!(
-                        "static={:?} moving={:?}",
-                        self.bodies[rb2_id].transform.translation,
-                        self.bodies[rb1_id].transform.translation,
+        self.force_generators.len() - 1
+    }
+
+    pub fn force_generator_ref(&self, handle: ForceGeneratorHandle) -> &dyn ForceGenerator {
+        self.force_generators[handle].as_ref()
+    }
+
+    pub fn force_generator_mut(&mut self, handle: ForceGeneratorHandle) -> &mut dyn ForceGenerator {
+        self.force_generators[handle].as_mut()
+    }
+
+    pub fn is_joint_enabled(&self, handle: JointHandle) -> bool {
+        self.joints[handle].enabled
+    }
+
+    /**
+     * A disabled joint is ignored by the solver, a broken joint is disabled
+     */
+    pub fn set_joint_enabled(&mut self, handle: JointHandle, enabled: bool) {
+        self.joints[handle].enabled = enabled;
+    }
+
+    /**
+     * The joint breaks when the force or the torque it applies during a step exceeds the threshold,
+     * None for an unbreakable joint (the default).
+     */
+    pub fn set_joint_break_threshold(
+        &mut self,
+        handle: JointHandle,
+        break_threshold: Option<BreakThreshold>,
+    ) {
+        self.joints[handle].break_threshold = break_threshold;
+    }
+
+    /**
+     * One rigidbody per bone, the first bone starts at `root`, and a joint between each bone and its parent.
+     * The bones linked by a joint, and the children of a same bone, do not collide with each other.
+     */
+    pub fn add_ragdoll(&mut self, root: P3, bones: &[Bone]) -> Ragdoll {
+        let mut starts: Vec<P3> = Vec::with_capacity(bones.len());
+        let mut ragdoll = Ragdoll {
+            rigidbody_ids: Vec::with_capacity(bones.len()),
+            joint_handles: Vec::with_capacity(bones.len()),
+        };
+
+        for (i, bone) in bones.iter().enumerate() {
+            debug_assert!(bone.parent.is_none_or(|parent| parent < i));
+            let start = match bone.parent {
+                Some(parent) => bones[parent].end(&starts[parent]),
+                None => root,
+            };
+            let (rb, co) = bone.rigidbody(&start);
+            let id = self.add_rigidbody(rb, co);
+
+            let joint_handle = bone.parent.map(|parent| {
+                let parent_id = ragdoll.rigidbody_ids[parent];
+                let joint = bone.joint(&self.bodies[parent_id], &self.bodies[id], &start);
+                self.add_joint(joint)
+            });
+            for (j, other) in bones[..i].iter().enumerate() {
+                if bone.parent == Some(j) || (bone.parent.is_some() && other.parent == bone.parent)
+                {
+                    self.collision_world.set_pair_ignored(
+                        self.bodies[ragdoll.rigidbody_ids[j]].collision_object_id(),
+                        self.bodies[id].collision_object_id(),
+                        true,
                     );
-                    for p in &cm.contact_infos.points {
-                        collision_solver::velocity_resolution_one_moving(
-                            &mut self.bodies[rb2_id],
-                            &p,
-                            -cm.contact_infos.normal_a_to_b,
-                            cm.contact_infos.penetration_distance,
-                        );
-                    }
                 }
-                (true, true) => {
-                    // println!("None mving");
-                }
-            };
+            }
+
+            starts.push(start);
+            ragdoll.rigidbody_ids.push(id);
+            ragdoll.joint_handles.push(joint_handle);
+        }
+
+        ragdoll
+    }
+
+    /**
+     * Events emitted by the last call to discrete_step() or advance()
+     */
+    pub fn events(&self) -> &[SimulationEvent] {
+        &self.events
+    }
+
+    pub fn drain_events(&mut self) -> Vec<SimulationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn rigidbody_ids(&self, cm: &ContactManifold) -> (usize, usize) {
+        let rb1_id = self
+            .collision_world
+            .collision_object_ref(cm.id_collision_object_a)
+            .unwrap()
+            .rigidbody_id
+            .unwrap();
+        let rb2_id = self
+            .collision_world
+            .collision_object_ref(cm.id_collision_object_b)
+            .unwrap()
+            .rigidbody_id
+            .unwrap();
+
+        (rb1_id, rb2_id)
+    }
+
+    /**
+     * Sequential impulses on the points of the manifolds, the impulses are accumulated
+     * on each point and reused at the next step (see collision_solver::ContactSolver).
+     * Only the velocities are solved, the penetration is corrected in discrete_step().
+     */
+    pub fn solve_contact_manifolds(&mut self) {
+        ContactSolver::new(self).solve();
+    }
+
+    /**
+     * Runs as many steps of `time_step` as fit in the elapsed time plus the time left by the previous call,
+     * at most `max_substeps`: beyond, the late time is dropped so a slow frame can't snowball.
+     * The events of all the steps are kept. Returns the number of steps.
+     */
+    pub fn advance(&mut self, elapsed_seconds: Real) -> usize {
+        self.accumulator += elapsed_seconds;
+        let mut events = Vec::new();
+        let mut substeps = 0;
+        while self.accumulator >= self.time_step && substeps < self.max_substeps {
+            self.discrete_step();
+            events.append(&mut self.events);
+            self.accumulator -= self.time_step;
+            substeps += 1;
+        }
+        if self.accumulator >= self.time_step {
+            self.accumulator %= self.time_step;
         }
+        self.events = events;
+
+        substeps
+    }
+
+    /**
+     * Fraction of a step not simulated yet by advance(), in [0, 1[
+     */
+    pub fn interpolation_factor(&self) -> Real {
+        self.accumulator / self.time_step
+    }
+
+    /**
+     * Transform to render the body with, interpolated between its last two states by the time left in advance()
+     */
+    pub fn render_transform(&self, id: usize) -> Transform {
+        self.bodies[id].interpolated_transform(self.interpolation_factor())
     }
 
     pub fn discrete_step(&mut self) {
-        // 1. On fait avancer les collisions
+        for rb in &mut self.bodies {
+            rb.store_previous_transform();
+        }
+
+        // 1. integre les vitesses avec les forces, le solveur travaille sur les vitesses de fin de pas
+        let free_states = match self.integrator {
+            Integrator::SemiImplicitEuler => {
+                self.apply_forces();
+                for i in 0..self.bodies.len() {
+                    self.integrate_rigidbody_velocities(i);
+                }
+                Vec::new()
+            }
+            Integrator::VelocityVerlet => integrators::velocity_verlet(self),
+            Integrator::RungeKutta4 => integrators::runge_kutta_4(self),
+        };
+
+        // 2. On fait avancer les collisions
         self.collision_world.step();
 
-        // 2. On calcul et on applique les impulsions
-        let n_ite = 8; //5-8
-        for _ in 0..n_ite {
-            self.solve_contact_manifolds();
-        }
+        // 3. On calcul et on applique les impulsions
+        let mut solver = ContactSolver::new(self);
+        solver.solve();
 
-        // 3. integre les nouveaux états( vitesses lin ang, position, orientation) et update inertia tensor on rigidbodies
+        // 4. integre les nouveaux états(position, orientation), puis corrige la pénétration
+        let constrained_bodies = solver.constrained_bodies();
+        solver.integrate_positions();
+        integrators::move_free_bodies(&mut self.bodies, &free_states, &constrained_bodies);
         for i in 0..self.bodies.len() {
-            self.step_rigidbody(i);
+            self.update_transform_collision_object(i);
+        }
+        for slot in &mut self.joints {
+            let (rb1_id, rb2_id) = slot.joint.rigidbody_ids();
+            let rb2 = rb2_id.map_or(&self.world_body, |id| &self.bodies[id]);
+            slot.joint.update_measures(&self.bodies[rb1_id], rb2);
         }
 
+        self.update_events();
+        self.break_joints();
+
         self.collision_world.clear_manifold();
+        for generator in &mut self.force_generators {
+            generator.end_step();
+        }
     }
 
-    pub fn set_appart(&mut self) {
+    /**
+     * Forces of the joints and of the force generators, the gravity is applied with the velocities
+     */
+    fn apply_forces(&mut self) {
+        for slot in self.joints.iter_mut().filter(|slot| slot.enabled) {
+            let (rb1_id, rb2_id) = slot.joint.rigidbody_ids();
+            let (rb1, rb2) = joint_bodies(&mut self.bodies, &mut self.world_body, rb1_id, rb2_id);
+            slot.joint.apply_forces(rb1, rb2);
+        }
+        for generator in &mut self.force_generators {
+            generator.apply_forces(&mut self.bodies, self.time_step);
+        }
+    }
+
+    /**
+     * Compares the contacts and the sensor overlaps of this step with the ones of the previous step.
+     * The ids of a pair are always given in the same order by the broadphase.
+     */
+    fn update_events(&mut self) {
+        self.events.clear();
+
+        let mut contacts = HashMap::with_capacity(self.previous_contacts.len());
         for cm in &self.collision_world.contact_manifolds {
-            let rb1_id = self
-                .collision_world
-                .collision_object_ref(cm.id_collision_object_a)
-                .unwrap()
-                .rigidbody_id
-                .unwrap();
-            let rb2_id = self
-                .collision_world
-                .collision_object_ref(cm.id_collision_object_b)
-                .unwrap()
-                .rigidbody_id
-                .unwrap();
-
-            let normal = cm.contact_infos.normal_a_to_b;
-            let distance = cm.contact_infos.penetration_distance;
-            let d = normal * distance;
-
-            match (self.bodies[rb1_id].is_static, self.bodies[rb2_id].is_static) {
-                (false, false) => {
-                    self.bodies[rb1_id].apply_displacement(-d);
-
-                    self.bodies[rb2_id].apply_displacement(d);
-                }
-                (false, true) => {
-                    self.bodies[rb1_id].apply_displacement(d);
-                }
-                (true, false) => {
-                    self.bodies[rb2_id].apply_displacement(d);
-                }
-                (true, true) => {
-                    // println!("None mving");
+            let max_impulse = cm
+                .accumulated_impulses
+                .iter()
+                .fold(ZERO, |max, impulse| helper::max(max, impulse.normal));
+            let key = (cm.id_collision_object_a, cm.id_collision_object_b);
+            let event = ContactEvent {
+                id_collision_object_a: key.0,
+                id_collision_object_b: key.1,
+                normal_a_to_b: cm.contact_infos.normal_a_to_b,
+                max_impulse,
+            };
+            if self.previous_contacts.contains_key(&key) {
+                self.events.push(SimulationEvent::ContactPersisted(event));
+            } else {
+                self.events.push(SimulationEvent::ContactStarted(event));
+            }
+            contacts.insert(key, cm.contact_infos.normal_a_to_b);
+        }
+        for (key, normal) in &self.previous_contacts {
+            if !contacts.contains_key(key) {
+                self.events
+                    .push(SimulationEvent::ContactStopped(ContactEvent {
+                        id_collision_object_a: key.0,
+                        id_collision_object_b: key.1,
+                        normal_a_to_b: *normal,
+                        max_impulse: ZERO,
+                    }));
+            }
+        }
+        self.previous_contacts = contacts;
+
+        let mut overlaps = HashSet::with_capacity(self.previous_overlaps.len());
+        for so in &self.collision_world.sensor_overlaps {
+            let key = (so.id_collision_object_a, so.id_collision_object_b);
+            let event = OverlapEvent {
+                id_collision_object_a: key.0,
+                id_collision_object_b: key.1,
+            };
+            if self.previous_overlaps.contains(&key) {
+                self.events.push(SimulationEvent::OverlapPersisted(event));
+            } else {
+                self.events.push(SimulationEvent::OverlapStarted(event));
+            }
+            overlaps.insert(key);
+        }
+        for key in &self.previous_overlaps {
+            if !overlaps.contains(key) {
+                self.events
+                    .push(SimulationEvent::OverlapStopped(OverlapEvent {
+                        id_collision_object_a: key.0,
+                        id_collision_object_b: key.1,
+                    }));
+            }
+        }
+        self.previous_overlaps = overlaps;
+    }
+
+    /**
+     * Disables the joints whose reaction exceeded their BreakThreshold during the step
+     */
+    fn break_joints(&mut self) {
+        for (handle, slot) in self.joints.iter_mut().enumerate() {
+            if let (true, Some(threshold)) = (slot.enabled, slot.break_threshold) {
+                let (linear, angular) = slot.joint.reaction_impulses();
+                let force = magnitude(&linear) / self.time_step;
+                let torque = magnitude(&angular) / self.time_step;
+                if threshold.is_exceeded(force, torque) {
+                    slot.enabled = false;
+                    self.events.push(SimulationEvent::JointBroken(JointEvent {
+                        handle,
+                        force,
+                        torque,
+                    }));
                 }
             }
         }
     }
 
     pub fn step_rigidbody(&mut self, id: usize) {
+        self.integrate_rigidbody_velocities(id);
+        self.integrate_rigidbody_state(id);
+    }
+
+    pub fn integrate_rigidbody_velocities(&mut self, id: usize) {
         let rb = &mut self.bodies[id];
-        if !rb.is_static {
-            rb.apply_gravity();
+        if rb.is_kinematic {
+            rb.update_kinematic_velocities(self.time_step);
+        } else if !rb.is_static {
+            rb.apply_gravity(self.gravity);
             rb.integrate_velocities(self.time_step);
+        }
+    }
+
+    pub fn integrate_rigidbody_state(&mut self, id: usize) {
+        let rb = &mut self.bodies[id];
+        if !rb.is_static {
             rb.integrate_state(self.time_step);
+        }
+        self.update_transform_collision_object(id);
+    }
 
-            // 4. Mettre à jour la position pour les shapes des collisions object associés aux rigid bodies
+    fn update_transform_collision_object(&mut self, id: usize) {
+        let rb = &self.bodies[id];
+        if !rb.is_static {
+            // Mettre à jour la position pour les shapes des collisions object associés aux rigid bodies
             self.collision_world
-                .update_transform_collision_object(rb.collision_object_id(), rb.transform.clone());
-
-            println!(
-                "  [{:?}]  {:?} mass={:?}",
-                rb.id,
-                rb.linear_velocity,
-                rb.mass()
-            );
+                .update_transform_collision_object(rb.collision_object_id(), rb.transform);
         }
     }
 }
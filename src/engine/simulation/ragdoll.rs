@@ -0,0 +1,108 @@
+use super::joints::axis_frame;
+use super::{ConeTwistJoint, HingeJoint, Joint, JointHandle, JointLimits, RigidBody};
+use crate::engine::collision::CollisionObject;
+use crate::engine::shapes::OBB;
+use crate::math::math_essentials::*;
+
+/**
+ * Joint between a bone and its parent, at the start of the bone.
+ * The angles are the ones of the bone relative to its parent.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BoneJoint {
+    // knee, elbow: `axis` in world space, in the rest pose
+    Hinge {
+        axis: Vec3,
+        limits: JointLimits,
+    },
+    // shoulder, hip, neck: twist around the bone
+    ConeTwist {
+        swing_span: Real,
+        twist_limits: JointLimits,
+    },
+}
+
+/**
+ * A bone starts at the end of its parent, or at the root of the ragdoll, and goes along `direction`.
+ * The parent must come before the bone in the skeleton, its joint is ignored without parent.
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bone {
+    pub parent: Option<usize>,
+    // in world space, in the rest pose
+    pub direction: Vec3,
+    pub length: Real,
+    pub radius: Real,
+    pub mass: Real,
+    pub joint: BoneJoint,
+}
+
+impl Bone {
+    pub fn new(
+        parent: Option<usize>,
+        direction: Vec3,
+        length: Real,
+        radius: Real,
+        mass: Real,
+        joint: BoneJoint,
+    ) -> Bone {
+        Bone {
+            parent,
+            direction: normalized(direction),
+            length,
+            radius,
+            mass,
+            joint,
+        }
+    }
+
+    pub fn end(&self, start: &P3) -> P3 {
+        start + &(self.direction * self.length)
+    }
+
+    /**
+     * Box along the bone, from `start`
+     */
+    pub fn rigidbody(&self, start: &P3) -> (RigidBody, CollisionObject) {
+        let transform = Transform {
+            rotation: axis_frame(&self.direction),
+            translation: start + &(self.direction * (self.length / TWO)),
+        };
+        let co = CollisionObject::new(Box::new(OBB::new(Vec3::new(
+            self.length / TWO,
+            self.radius,
+            self.radius,
+        ))));
+
+        (RigidBody::new(transform, self.mass, false), co)
+    }
+
+    /**
+     * Joint from the parent (rb1) to the bone (rb2), at `start`
+     */
+    pub fn joint(&self, parent: &RigidBody, rb: &RigidBody, start: &P3) -> Box<dyn Joint> {
+        match self.joint {
+            BoneJoint::Hinge { axis, limits } => Box::new(
+                HingeJoint::new(parent, Some(rb), *start, axis)
+                    .with_limits(limits.lower, limits.upper),
+            ),
+            BoneJoint::ConeTwist {
+                swing_span,
+                twist_limits,
+            } => Box::new(
+                ConeTwistJoint::new(parent, Some(rb), *start, self.direction)
+                    .with_swing_limit(swing_span)
+                    .with_twist_limits(twist_limits.lower, twist_limits.upper),
+            ),
+        }
+    }
+}
+
+/**
+ * What SimulationWorld::add_ragdoll() created, by bone.
+ * The joint of a bone without parent is None.
+ */
+pub struct Ragdoll {
+    pub rigidbody_ids: Vec<usize>,
+    pub joint_handles: Vec<Option<JointHandle>>,
+}
@@ -1,6 +1,5 @@
-use super::consts;
 use crate::engine::collision::CollisionObject;
-use crate::math::{math_essentials::*, Mat3, Quaternion};
+use crate::math::{math_essentials::*, rotation_vector, Mat3, Quaternion};
 
 pub struct RigidBody {
     pub linear_velocity: Vec3,
@@ -16,9 +15,18 @@ pub struct RigidBody {
     mass: Real,
     inv_mass: Real,
     pub transform: Transform,
+    // at the beginning of the last step
+    previous_transform: Transform,
     pub id: usize,
-    pub restitution_coef: Real,
     pub is_static: bool,
+    // infinite mass, moved by its velocities or its kinematic target
+    pub is_kinematic: bool,
+    // multiplies the gravity of the world, 0 to ignore it
+    pub gravity_scale: Real,
+    // s^-1, the velocities decay by exp(-damping * dt) at each step
+    pub linear_damping: Real,
+    pub angular_damping: Real,
+    kinematic_target: Option<Transform>,
     center_of_mass: P3,
     local_center_of_mass: P3,
 }
@@ -54,9 +62,14 @@ impl RigidBody {
             mass,
             inv_mass,
             transform,
+            previous_transform: transform,
             id: 0,
-            restitution_coef: 0.95,
             is_static,
+            is_kinematic: false,
+            gravity_scale: ONE,
+            linear_damping: ZERO,
+            angular_damping: ZERO,
+            kinematic_target: None,
             center_of_mass: transform.translation,
             local_center_of_mass: P3::origin(),
         }
@@ -88,6 +101,7 @@ impl RigidBody {
         self.inv_mass = 1.0 / self.mass;
         self.inertia_matrix = co.shape.compute_inertia_matrix(self.mass);
         self.inv_inertia_matrix = self.inertia_matrix.inverse();
+        self.update_inv_inertia_tensor();
     }
 
     pub fn inertia_tensor(&self) -> Mat3 {
@@ -100,12 +114,14 @@ impl RigidBody {
     pub fn link_and_set_collision_object(&mut self, co: &mut CollisionObject) {
         co.rigidbody_id = Some(self.id);
         co.is_static = self.is_static;
+        co.is_kinematic = self.is_kinematic;
         self.collision_object_id = Some(co.id);
-        co.shape.set_transform(self.transform.clone());
-        if !self.is_static {
+        co.shape.set_transform(self.transform);
+        if !self.is_static && !self.is_kinematic {
             // self.local_center_of_mass = ...
             self.inertia_matrix = co.shape.compute_inertia_matrix(self.mass);
             self.inv_inertia_matrix = self.inertia_matrix.inverse();
+            self.update_inv_inertia_tensor();
         }
     }
 
@@ -117,12 +133,78 @@ impl RigidBody {
         self.inv_mass = ZERO;
     }
 
+    /**
+     * Call before linking the collision object.
+     * The body is moved by its velocities, set by the user or by set_kinematic_target(),
+     * and pushes the dynamic bodies without being pushed.
+     */
+    pub fn set_kinematic(&mut self) {
+        self.is_kinematic = true;
+        self.translation_moving_axis = Vec3::value(ZERO);
+        self.rotation_moving_axis = Vec3::value(ZERO);
+        self.mass = ZERO;
+        self.inv_mass = ZERO;
+        self.inertia_matrix = Mat3::zero();
+        self.inv_inertia_matrix = Mat3::zero();
+        self.inv_inertia_tensor = Mat3::zero();
+    }
+
+    /**
+     * The kinematic body reaches `target` at the end of the next step, then stays there.
+     */
+    pub fn set_kinematic_target(&mut self, target: Transform) {
+        debug_assert!(self.is_kinematic);
+        self.kinematic_target = Some(target);
+    }
+
+    /**
+     * Drives the kinematic body by its velocities instead of its target
+     */
+    pub fn set_kinematic_velocities(&mut self, linear_velocity: Vec3, angular_velocity: Vec3) {
+        debug_assert!(self.is_kinematic);
+        self.kinematic_target = None;
+        self.linear_velocity = linear_velocity;
+        self.angular_velocity = angular_velocity;
+    }
+
+    /**
+     * The velocities reaching the kinematic target in `dt`, if any
+     */
+    pub fn update_kinematic_velocities(&mut self, dt: Real) {
+        if let Some(target) = self.kinematic_target {
+            self.linear_velocity = (target.translation - self.transform.translation) / dt;
+            self.angular_velocity =
+                rotation_vector(&(target.rotation * self.transform.rotation.transposed())) / dt;
+        }
+    }
+
+    /**
+     * The forces, then the damping
+     */
     pub fn integrate_velocities(&mut self, dt: Real) {
-        let coef_air_resistance = 0.97;
-        self.linear_velocity += self.total_force * self.inv_mass * dt * coef_air_resistance;
-        self.angular_velocity += self.inv_inertia_tensor * self.total_torque * dt;
+        let (linear_acceleration, angular_acceleration) = self.take_accelerations();
+        self.linear_velocity += linear_acceleration * dt;
+        self.angular_velocity += angular_acceleration * dt;
+        self.apply_damping(dt);
+    }
+
+    /**
+     * Linear and angular accelerations from the forces applied since the last call, the forces are cleared
+     */
+    pub fn take_accelerations(&mut self) -> (Vec3, Vec3) {
+        let accelerations = (
+            self.total_force * self.inv_mass,
+            self.inv_inertia_tensor * self.total_torque,
+        );
         self.total_force = Vec3::zeros();
         self.total_torque = Vec3::zeros();
+
+        accelerations
+    }
+
+    pub fn apply_damping(&mut self, dt: Real) {
+        self.linear_velocity *= (-self.linear_damping * dt).exp();
+        self.angular_velocity *= (-self.angular_damping * dt).exp();
     }
 
     /**
@@ -134,13 +216,66 @@ impl RigidBody {
      * https://perso.liris.cnrs.fr/florence.zara/Web/media/files/M2-Animation/Chap4-RigidBody.pdf
      */
     pub fn integrate_state(&mut self, dt: Real) {
+        // the velocities only approximate the rotation to the target
+        if let Some(target) = self.kinematic_target {
+            self.set_transform(target);
+            return;
+        }
         self.apply_displacement(self.linear_velocity * dt);
         self.apply_rotation_vector(Quaternion::from_vec(&self.angular_velocity) * dt);
+    }
 
-        // update center of mass
+    /**
+     * Moves the body, without changing its velocities
+     */
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
         self.center_of_mass = self.local_center_of_mass + self.transform.translation;
+        self.update_inv_inertia_tensor();
+    }
+
+    pub fn previous_transform(&self) -> &Transform {
+        &self.previous_transform
+    }
+
+    /**
+     * Called at the beginning of each step
+     */
+    pub fn store_previous_transform(&mut self) {
+        self.previous_transform = self.transform;
+    }
+
+    /**
+     * Between the previous transform (`alpha` = 0) and the current one (`alpha` = 1),
+     * the translation is linearly interpolated and the rotation normalized-linearly interpolated.
+     */
+    pub fn interpolated_transform(&self, alpha: Real) -> Transform {
+        let q0 = Quaternion::from_mat3(&self.previous_transform.rotation);
+        let mut q1 = Quaternion::from_mat3(&self.transform.rotation);
+        // shortest path
+        if q0.w * q1.w + q0.x * q1.x + q0.y * q1.y + q0.z * q1.z < ZERO {
+            q1 = q1 * -ONE;
+        }
+        let q = q0 * (ONE - alpha) + q1 * alpha;
+
+        Transform {
+            rotation: (q / q.magnitude()).to_mat3(),
+            translation: self.previous_transform.translation * (ONE - alpha)
+                + self.transform.translation * alpha,
+        }
+    }
+
+    /**
+     * Neither static nor kinematic, it is moved by the forces and the contacts
+     */
+    pub fn is_dynamic(&self) -> bool {
+        !self.is_static && !self.is_kinematic
+    }
 
-        // update inverse inertia tensor
+    /**
+     * The inverse inertia tensor in world space, follows the orientation
+     */
+    fn update_inv_inertia_tensor(&mut self) {
         self.inv_inertia_tensor =
             self.transform.rotation * (self.inv_inertia_matrix * self.transform.rotation.inverse());
     }
@@ -158,8 +293,11 @@ impl RigidBody {
         self.apply_torque(cross(&(point - self.center_of_mass), &force));
     }
 
-    pub fn apply_gravity(&mut self) {
-        self.apply_central_force(consts::GRAVITY_VECTOR * self.mass);
+    /**
+     * `gravity` is the acceleration of the world, scaled by the gravity scale of the body
+     */
+    pub fn apply_gravity(&mut self, gravity: Vec3) {
+        self.apply_central_force(gravity * (self.mass * self.gravity_scale));
     }
     /**
      * an impulse is a force
@@ -176,6 +314,7 @@ impl RigidBody {
      */
     pub fn apply_displacement(&mut self, translation: Vec3) {
         self.transform.translation += translation;
+        self.center_of_mass = self.local_center_of_mass + self.transform.translation;
     }
 
     /**
@@ -187,6 +326,9 @@ impl RigidBody {
     pub fn apply_rotation_vector(&mut self, quat_from_vec: Quaternion) {
         // Quaternion derivative
         let q = Quaternion::from_mat3(&self.transform.rotation);
-        self.transform.rotation = q.quaternion_derivative(quat_from_vec).to_mat3();
+        let q = q.quaternion_derivative(quat_from_vec);
+        // keeps a rotation, the derivative alone makes the quaternion grow
+        self.transform.rotation = (q / q.magnitude()).to_mat3();
+        self.update_inv_inertia_tensor();
     }
 }
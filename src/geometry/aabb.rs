@@ -0,0 +1,121 @@
+use crate::math::math_essentials::*;
+
+/**
+ * Axis-Aligned Bounding Box, expressed in world space.
+ * Touching boxes are considered as overlapping, like in the SAT.
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    pub min: P3,
+    pub max: P3,
+}
+
+impl Aabb {
+    pub fn new(min: P3, max: P3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    pub fn from_center_half_extents(center: P3, half_extents: Vec3) -> Aabb {
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /**
+     * Bounds covering the whole space, used for unbounded shapes like the Plane.
+     */
+    pub fn infinite() -> Aabb {
+        Aabb {
+            min: Vec3::value(Real::NEG_INFINITY),
+            max: Vec3::value(Real::INFINITY),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        (0..3).all(|i| self.min[i].is_finite() && self.max[i].is_finite())
+    }
+
+    /**
+     * Is `o` entirely inside self
+     */
+    pub fn contains(&self, o: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= o.min[i] && o.max[i] <= self.max[i])
+    }
+
+    /**
+     * Smallest box containing both self and `o`
+     */
+    pub fn merged(&self, o: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec3::new(
+                helper::min(self.min[0], o.min[0]),
+                helper::min(self.min[1], o.min[1]),
+                helper::min(self.min[2], o.min[2]),
+            ),
+            max: Vec3::new(
+                helper::max(self.max[0], o.max[0]),
+                helper::max(self.max[1], o.max[1]),
+                helper::max(self.max[2], o.max[2]),
+            ),
+        }
+    }
+
+    /**
+     * Grow the box by `margin` on every side
+     */
+    pub fn fattened(&self, margin: Real) -> Aabb {
+        Aabb {
+            min: self.min - Vec3::value(margin),
+            max: self.max + Vec3::value(margin),
+        }
+    }
+
+    pub fn surface_area(&self) -> Real {
+        let d = self.max - self.min;
+        TWO * (d[0] * d[1] + d[1] * d[2] + d[2] * d[0])
+    }
+
+    pub fn intersects(&self, o: &Aabb) -> bool {
+        self.min[0] <= o.max[0]
+            && o.min[0] <= self.max[0]
+            && self.min[1] <= o.max[1]
+            && o.min[1] <= self.max[1]
+            && self.min[2] <= o.max[2]
+            && o.min[2] <= self.max[2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_intersects() {
+        let a = Aabb::from_center_half_extents(P3::origin(), Vec3::ones());
+        let b = Aabb::from_center_half_extents(P3::new(1.5, ZERO, ZERO), Vec3::ones());
+        let c = Aabb::from_center_half_extents(P3::new(2.0, ZERO, ZERO), Vec3::ones());
+        let d = Aabb::from_center_half_extents(P3::new(ZERO, 3.0, ZERO), Vec3::ones());
+
+        assert!(a.intersects(&b));
+        // touching
+        assert!(a.intersects(&c));
+        assert!(!a.intersects(&d));
+        assert!(Aabb::infinite().intersects(&d));
+    }
+
+    #[test]
+    fn aabb_merged_contains() {
+        let a = Aabb::from_center_half_extents(P3::origin(), Vec3::ones());
+        let b = Aabb::from_center_half_extents(P3::new(3.0, ZERO, ZERO), Vec3::ones());
+        let m = a.merged(&b);
+
+        assert!(m.contains(&a));
+        assert!(m.contains(&b));
+        assert!(!a.contains(&m));
+        assert_eq!(m.min, P3::new(-ONE, -ONE, -ONE));
+        assert_eq!(m.max, P3::new(4.0, ONE, ONE));
+        assert!(a.fattened(0.1).contains(&a));
+        assert_eq!(a.surface_area(), 24.0);
+    }
+}
@@ -1,4 +1,4 @@
+pub mod aabb;
 pub mod geometry_traits;
 pub mod sat;
 pub mod helper;
-
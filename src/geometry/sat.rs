@@ -8,6 +8,9 @@ pub trait SAT {
     fn separating_axis(&self) -> Vec<Vec3>;
 }
 
+// sinus of the angle under which two edges are considered parallel
+const PARALLEL_EDGES_TOLERANCE: Real = 1e-3;
+
 pub struct Projection {
     min: Real,
     max: Real,
@@ -197,7 +200,12 @@ pub fn sat_3D<T: PolyhedronTrait>(shape_A: &T, shape_B: &T) -> Option<SAT3DResul
             let mut cross = cross(&direction_edge_A, &direction_edge_B);
             let norm = magnitude(&cross);
 
-            if norm == ZERO {
+            // parallel edges, the axis would only be noise
+            if norm
+                <= PARALLEL_EDGES_TOLERANCE
+                    * magnitude(&direction_edge_A)
+                    * magnitude(&direction_edge_B)
+            {
                 continue;
             }
 
@@ -4,8 +4,8 @@ extern crate angine;
 extern crate kiss3d;
 
 use angine::engine::{
-    collision::collision_object::CollisionObject, shapes, simulation::RigidBody,
-    simulation::SimulationWorld,
+    collision::collision_object::CollisionObject, shapes, simulation::consts,
+    simulation::RigidBody, simulation::SimulationWorld,
 };
 use angine::math::math_essentials::*;
 use angine::math::Quaternion as AngineQuat;
@@ -17,10 +17,9 @@ use kiss3d::window::Window;
 
 pub fn update_state(
     node: &mut kiss3d::scene::SceneNode,
-    angine_object: &RigidBody,
+    transform: Transform,
     base_rotation: UnitQuaternion<f32>,
 ) {
-    let transform = angine_object.transform;
     let q = AngineQuat::from_mat3(&transform.rotation);
     let qq = Quaternion::new(q.x, q.y, q.z, q.w);
     let unit_q = UnitQuaternion::from_quaternion(qq);
@@ -100,56 +99,41 @@ fn main() {
     plane.set_color(0.0, 1.0, 0.0);
     wireframe(&mut plane);
 
-    update_state(
-        &mut cube,
-        simulation_world.rigidbody_ref(obb_id),
-        UnitQuaternion::<f32>::identity(),
-    );
-    update_state(&mut plane, simulation_world.rigidbody_ref(plane_id), unit_q);
-    // update_state(
-    //     &mut s1,
-    //     simulation_world.rigidbody_ref(sphere1_id),
-    //     UnitQuaternion::<f32>::identity(),
-    // );
-    // update_state(
-    //     &mut s2,
-    //     simulation_world.rigidbody_ref(sphere2_id),
-    //     UnitQuaternion::<f32>::identity(),
-    // );
-    simulation_world.pre_start_set_appart();
-
-    window.set_light(Light::StickToCamera);
-    let mut pause = true;
-    let mut step = || {
-        let t_start = std::time::Instant::now();
-        simulation_world.discrete_step();
+    let update_nodes = |simulation_world: &SimulationWorld,
+                        cube: &mut kiss3d::scene::SceneNode,
+                        plane: &mut kiss3d::scene::SceneNode| {
         update_state(
-            &mut cube,
-            simulation_world.rigidbody_ref(obb_id),
+            cube,
+            simulation_world.render_transform(obb_id),
             UnitQuaternion::<f32>::identity(),
         );
         // even if plane is static in angine
-        update_state(&mut plane, simulation_world.rigidbody_ref(plane_id), unit_q);
+        update_state(plane, simulation_world.render_transform(plane_id), unit_q);
         // update_state(
         //     &mut s1,
-        //     simulation_world.rigidbody_ref(sphere1_id),
+        //     simulation_world.render_transform(sphere1_id),
         //     UnitQuaternion::<f32>::identity(),
         // );
         // update_state(
         //     &mut s2,
-        //     simulation_world.rigidbody_ref(sphere2_id),
+        //     simulation_world.render_transform(sphere2_id),
         //     UnitQuaternion::<f32>::identity(),
         // );
-        let elapsed = t_start.elapsed();
-        let i = std::cmp::min(16 as u32, elapsed.as_millis() as u32);
-        let i = std::cmp::max(i, 0 as u32);
-        std::thread::sleep_ms(16 - i);
     };
+    update_nodes(&simulation_world, &mut cube, &mut plane);
+
+    window.set_light(Light::StickToCamera);
+    let mut pause = true;
+    let mut last_frame = std::time::Instant::now();
 
     loop {
+        // fixed steps for the time elapsed since the last frame, the bodies are drawn between the last two steps
+        let elapsed = last_frame.elapsed().as_secs_f32();
+        last_frame = std::time::Instant::now();
         if !pause {
-            step();
+            simulation_world.advance(elapsed);
         }
+        update_nodes(&simulation_world, &mut cube, &mut plane);
 
         if !window.render() {
             break;
@@ -162,7 +146,7 @@ fn main() {
                         pause = !pause;
                     }
                     Key::N => {
-                        step();
+                        simulation_world.advance(consts::DELTA_TIME);
                     }
                     Key::R => {}
                     Key::W => {}
@@ -7,6 +7,7 @@ pub mod vector;
 
 pub use helper::*;
 pub use matrix::Mat3;
+pub use quaternion::rotation_vector;
 pub use quaternion::Quaternion;
 pub use transform::Rotation;
 pub use transform::Transform;
@@ -1,4 +1,4 @@
-use super::{helper, Directions, Mat3, Real, Vec3, ONE, TWO, ZERO};
+use super::{helper, vector::magnitude, Directions, Mat3, Real, Vec3, ONE, TWO, ZERO};
 use std::ops::{Add, Div, Mul, MulAssign};
 
 /**
@@ -189,6 +189,21 @@ impl Quaternion {
     }
 }
 
+/**
+ * Axis times angle of the rotation, the angle in [0, PI]
+ */
+pub fn rotation_vector(rotation: &Mat3) -> Vec3 {
+    // w is positive
+    let q = Quaternion::from_mat3(rotation);
+    let v = Vec3::new(q.x, q.y, q.z);
+    let sin = magnitude(&v);
+    if sin > ZERO {
+        v * (TWO * sin.atan2(q.w) / sin)
+    } else {
+        Vec3::zeros()
+    }
+}
+
 impl Add for Quaternion {
     type Output = Self;
 
@@ -1,9 +1,19 @@
 extern crate angine;
 use angine::engine::{
-    collision::collision_object::CollisionObject, collision::CollisionWorld, shapes, shapes::Shape,
-    shapes::ShapeType, simulation::RigidBody, simulation::SimulationWorld,
+    broadphase::BroadPhaseType, collision::collision_object::CollisionObject,
+    collision::CollisionWorld, collision::CombineRule, collision::Material, shapes, shapes::Shape,
+    shapes::ShapeType, simulation::consts, simulation::AnchoredSpring, simulation::AxisDrive,
+    simulation::AxisMotion, simulation::BallSocketJoint, simulation::Bone, simulation::BoneJoint,
+    simulation::BreakThreshold, simulation::ConeTwistJoint, simulation::DistanceJoint,
+    simulation::Explosion, simulation::FixedJoint, simulation::HingeJoint, simulation::Integrator,
+    simulation::JointLimits, simulation::PositionCorrection, simulation::PrismaticJoint,
+    simulation::QuadraticDrag, simulation::RigidBody, simulation::SimulationEvent,
+    simulation::SimulationWorld, simulation::SixDofJoint, simulation::SpringJoint,
+    simulation::WindVolume,
 };
+use angine::geometry::aabb::Aabb;
 use angine::math::math_essentials::*;
+use assert_approx_eq::assert_approx_eq;
 // use angine::math::Quaternion as AngineQuat;
 
 #[test]
@@ -313,3 +323,1342 @@ fn impulsion_and_normal() {
         assert!(dot(&normalized(plane2obb_ttwo), &plane2obb_tone) > 0.0);
     }
 }
+
+#[test]
+fn broadphase_only_overlapping_pairs() {
+    let mut collision_world = CollisionWorld::new();
+    // a row of spheres, each one touching only its neighbours
+    for i in 0..10 {
+        let mut co = CollisionObject::new(Box::new(shapes::Sphere::new(1.0)));
+        co.shape
+            .set_transform(Transform::translation(P3::new(1.5 * i as Real, 0.0, 0.0)));
+        collision_world.add_collision_object(co);
+    }
+
+    collision_world.step();
+    assert_eq!(collision_world.contact_manifolds.len(), 9);
+    collision_world.clear_manifold();
+
+    // moving the last one away removes one contact
+    collision_world
+        .update_transform_collision_object(9, Transform::translation(P3::new(100.0, 0.0, 0.0)));
+    collision_world.step();
+    assert_eq!(collision_world.contact_manifolds.len(), 8);
+}
+
+#[test]
+fn broadphases_give_same_contacts() {
+    for broadphase_type in [
+        BroadPhaseType::SweepAndPrune,
+        BroadPhaseType::DynamicAabbTree,
+    ] {
+        let mut collision_world = CollisionWorld::with_broadphase(broadphase_type);
+        for i in 0..10 {
+            let mut co = CollisionObject::new(Box::new(shapes::Sphere::new(1.0)));
+            co.shape
+                .set_transform(Transform::translation(P3::new(1.5 * i as Real, 0.0, 0.0)));
+            collision_world.add_collision_object(co);
+        }
+        let mut plane_co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+        plane_co
+            .shape
+            .set_transform(Transform::translation(P3::new(0.0, -0.5, 0.0)));
+        plane_co.is_static = true;
+        collision_world.add_collision_object(plane_co);
+
+        collision_world.step();
+        assert_eq!(collision_world.contact_manifolds.len(), 9 + 10);
+        collision_world.clear_manifold();
+
+        let mut r = collision_world
+            .query_aabb(&Aabb::new(P3::new(-0.5, 0.0, -0.5), P3::new(0.4, 0.5, 0.5)));
+        r.sort();
+        assert_eq!(r, vec![0, 10]);
+
+        collision_world.set_broadphase(BroadPhaseType::SweepAndPrune);
+        collision_world.step();
+        assert_eq!(collision_world.contact_manifolds.len(), 9 + 10);
+    }
+}
+
+#[test]
+fn collision_groups_and_pair_filter() {
+    const DEBRIS: u32 = 1 << 1;
+    const PLAYER: u32 = 1 << 2;
+
+    // three overlapping spheres : two debris and a player
+    let mut collision_world = CollisionWorld::new();
+    let mut debris_1 = CollisionObject::new(Box::new(shapes::Sphere::new(1.0)));
+    debris_1.set_collision_groups(DEBRIS, PLAYER);
+    let mut debris_2 = CollisionObject::new(Box::new(shapes::Sphere::new(1.0)));
+    debris_2.set_collision_groups(DEBRIS, PLAYER);
+    debris_2
+        .shape
+        .set_transform(Transform::translation(P3::new(0.5, 0.0, 0.0)));
+    let mut player = CollisionObject::new(Box::new(shapes::Sphere::new(1.0)));
+    player.set_collision_groups(PLAYER, DEBRIS);
+    player
+        .shape
+        .set_transform(Transform::translation(P3::new(0.0, 0.5, 0.0)));
+
+    collision_world.add_collision_object(debris_1);
+    collision_world.add_collision_object(debris_2);
+    let player_id = collision_world.add_collision_object(player);
+
+    // debris vs debris is filtered
+    collision_world.step();
+    assert_eq!(collision_world.contact_manifolds.len(), 2);
+    collision_world.clear_manifold();
+
+    // the player refuses the collision with the object 0
+    collision_world
+        .collision_object_mut(player_id)
+        .unwrap()
+        .pair_filter = Some(|_, other| other.id != 0);
+    collision_world.step();
+    assert_eq!(collision_world.contact_manifolds.len(), 1);
+    let cm = &collision_world.contact_manifolds[0];
+    assert_eq!(
+        (cm.id_collision_object_a, cm.id_collision_object_b),
+        (1, player_id)
+    );
+}
+
+#[test]
+fn sensor_reports_overlap_without_contact() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // a static pickup zone (obb_sphere has no contact generation)
+    let zone_co = CollisionObject::new_sensor(Box::new(shapes::OBB::new(Vec3::value(2.0))));
+    let zone_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    let zone_id = simulation_world.add_rigidbody(zone_rb, zone_co);
+
+    // a sphere falling through it
+    let sphere_co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let sphere_rb = RigidBody::new(Transform::translation(P3::new(0.0, 1.0, 0.0)), 1.0, false);
+    let sphere_id = simulation_world.add_rigidbody(sphere_rb, sphere_co);
+
+    simulation_world.collision_world.step();
+    assert_eq!(simulation_world.collision_world.contact_manifolds.len(), 0);
+    assert_eq!(simulation_world.collision_world.sensor_overlaps.len(), 1);
+    let overlap = simulation_world.collision_world.sensor_overlaps[0];
+    assert_eq!(
+        overlap.id_collision_object_a,
+        simulation_world
+            .rigidbody_ref(zone_id)
+            .collision_object_id()
+    );
+    assert_eq!(
+        overlap.id_collision_object_b,
+        simulation_world
+            .rigidbody_ref(sphere_id)
+            .collision_object_id()
+    );
+    simulation_world.collision_world.clear_manifold();
+
+    // the sphere isn't stopped by the sensor
+    let y = simulation_world
+        .rigidbody_ref(sphere_id)
+        .transform
+        .translation
+        .y();
+    simulation_world.discrete_step();
+    assert!(
+        simulation_world
+            .rigidbody_ref(sphere_id)
+            .transform
+            .translation
+            .y()
+            < y
+    );
+}
+
+#[test]
+fn contact_events_stream() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // without restitution, the box comes to rest on the plane
+    let material = Material::new(0.6, 0.4, 0.0, 1000.0);
+    let mut obb_co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(ONE))));
+    obb_co.material = material;
+    let obb_rb = RigidBody::new(Transform::translation(P3::new(0.0, 1.5, 0.0)), 1.0, false);
+    let obb_id = simulation_world.add_rigidbody(obb_rb, obb_co);
+    let obb_co_id = simulation_world.rigidbody_ref(obb_id).collision_object_id();
+
+    let mut plane_co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+    plane_co.material = material;
+    let plane_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    let plane_id = simulation_world.add_rigidbody(plane_rb, plane_co);
+    let plane_co_id = simulation_world
+        .rigidbody_ref(plane_id)
+        .collision_object_id();
+
+    let mut events = Vec::new();
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        events.extend(simulation_world.drain_events());
+    }
+
+    let started = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::ContactStarted(_)))
+        .expect("the box should touch the plane");
+    if let SimulationEvent::ContactStarted(c) = events[started] {
+        assert_eq!(c.id_collision_object_a, obb_co_id);
+        assert_eq!(c.id_collision_object_b, plane_co_id);
+        assert!(c.max_impulse > ZERO);
+        assert!(dot(&c.normal_a_to_b, &Directions::down()) > ZERO);
+    }
+    // no Started twice in a row without a Stopped
+    let mut in_contact = false;
+    for e in &events {
+        match e {
+            SimulationEvent::ContactStarted(_) => {
+                assert!(!in_contact);
+                in_contact = true;
+            }
+            SimulationEvent::ContactPersisted(_) => assert!(in_contact),
+            SimulationEvent::ContactStopped(c) => {
+                assert!(in_contact);
+                assert_eq!(c.max_impulse, ZERO);
+                in_contact = false;
+            }
+            _ => panic!("no sensor in this world"),
+        }
+    }
+
+    // resting on the plane at the end
+    assert!(in_contact);
+
+    // moved away, the contact stops once the collision object follows the rigidbody
+    let rb = simulation_world.rigidbody_mut(obb_id);
+    rb.transform.translation = P3::new(0.0, 10.0, 0.0);
+    rb.linear_velocity = Vec3::zeros();
+    simulation_world.discrete_step();
+    simulation_world.discrete_step();
+    assert!(simulation_world
+        .events()
+        .iter()
+        .any(|e| matches!(e, SimulationEvent::ContactStopped(_))));
+}
+
+#[test]
+fn sensor_overlap_events() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    let zone_co = CollisionObject::new_sensor(Box::new(shapes::OBB::new(Vec3::value(ONE))));
+    let zone_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    simulation_world.add_rigidbody(zone_rb, zone_co);
+
+    let sphere_co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let sphere_rb = RigidBody::new(Transform::translation(P3::new(0.0, 2.0, 0.0)), 1.0, false);
+    simulation_world.add_rigidbody(sphere_rb, sphere_co);
+
+    let mut events = Vec::new();
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        events.extend(simulation_world.drain_events());
+    }
+
+    let started = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::OverlapStarted(_)))
+        .unwrap();
+    let persisted = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::OverlapPersisted(_)))
+        .unwrap();
+    let stopped = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::OverlapStopped(_)))
+        .unwrap();
+    assert!(started < persisted && persisted < stopped);
+    assert!(events
+        .iter()
+        .all(|e| !matches!(e, SimulationEvent::ContactStarted(_))));
+}
+
+#[test]
+fn box_stack_settles() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    let plane_co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+    let plane_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    simulation_world.add_rigidbody(plane_rb, plane_co);
+
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        let obb_co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.5))));
+        let obb_rb = RigidBody::new(
+            Transform::translation(P3::new(0.0, 0.5 + i as Real, 0.0)),
+            1.0,
+            false,
+        );
+        ids.push(simulation_world.add_rigidbody(obb_rb, obb_co));
+    }
+
+    for _ in 0..300 {
+        simulation_world.discrete_step();
+    }
+
+    // the points persist, their impulses are reused from a step to another
+    let bottom_co = simulation_world.rigidbody_ref(ids[0]).collision_object_id();
+    let above_co = simulation_world.rigidbody_ref(ids[1]).collision_object_id();
+    let manifold = simulation_world
+        .collision_world
+        .persistent_manifold(bottom_co, above_co)
+        .expect("the boxes should be in contact");
+    assert_eq!(
+        manifold.contact_infos.points.len(),
+        manifold.contact_infos.feature_ids.len()
+    );
+    assert!(manifold
+        .accumulated_impulses
+        .iter()
+        .any(|i| i.normal > ZERO));
+
+    // the stack is at rest where it was built
+    for (i, id) in ids.iter().enumerate() {
+        let rb = simulation_world.rigidbody_ref(*id);
+        assert!(magnitude(&rb.linear_velocity) < 0.01);
+        assert!(magnitude(&rb.angular_velocity) < 0.01);
+        let drift = rb.transform.translation - P3::new(0.0, 0.5 + i as Real, 0.0);
+        assert!(magnitude(&drift) < 0.05);
+    }
+}
+
+fn ramp_world(friction: Real, shape: Box<dyn Shape>) -> (SimulationWorld, usize) {
+    let mut simulation_world = SimulationWorld::new(None);
+    let rotation = Rotation::Z(helper::angle_2_rad(20.0));
+    let normal = rotation * Directions::up();
+
+    let material = Material::new(friction, friction, ZERO, 1000.0);
+
+    let mut plane_co = CollisionObject::new(Box::new(shapes::Plane::new(normal)));
+    plane_co.material = material;
+    let plane_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    simulation_world.add_rigidbody(plane_rb, plane_co);
+
+    let mut co = CollisionObject::new(shape);
+    co.material = material;
+    let rb = RigidBody::new(
+        Transform::new(Vec3::ones(), rotation, normal * 0.5),
+        1.0,
+        false,
+    );
+    let id = simulation_world.add_rigidbody(rb, co);
+
+    (simulation_world, id)
+}
+
+#[test]
+fn box_on_ramp_friction() {
+    // tan(20°) = 0.36
+    let (mut sliding, sliding_id) = ramp_world(ZERO, Box::new(shapes::OBB::new(Vec3::value(0.5))));
+    let (mut sticking, sticking_id) = ramp_world(0.6, Box::new(shapes::OBB::new(Vec3::value(0.5))));
+    for _ in 0..120 {
+        sliding.discrete_step();
+        sticking.discrete_step();
+    }
+
+    assert!(magnitude(&sliding.rigidbody_ref(sliding_id).linear_velocity) > 5.0);
+    assert!(magnitude(&sticking.rigidbody_ref(sticking_id).linear_velocity) < 0.01);
+}
+
+#[test]
+fn sphere_rolls_on_ramp() {
+    let (mut simulation_world, id) = ramp_world(0.6, Box::new(shapes::Sphere::new(0.5)));
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+    }
+
+    let rb = simulation_world.rigidbody_ref(id);
+    let speed = magnitude(&rb.linear_velocity);
+    assert!(speed > 1.0);
+    // rolling without slipping
+    assert!((magnitude(&rb.angular_velocity) * 0.5 - speed).abs() < 0.05 * speed);
+}
+
+#[test]
+fn material_combine_rules_in_contacts() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    let mut clay_co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    clay_co.material = Material::new(0.6, 0.4, ZERO, 1500.0)
+        .with_combine_rules(CombineRule::Average, CombineRule::Multiply);
+    let clay_mass = clay_co.mass_from_density();
+    assert_approx_eq!(
+        clay_mass,
+        1500.0 * 4.0 / 3.0 * std::f32::consts::PI * 0.125,
+        1e-2
+    );
+    let mut clay_rb = RigidBody::new(Transform::identity(), clay_mass, false);
+    clay_rb.linear_velocity = Vec3::new(3.0, ZERO, ZERO);
+    let clay_id = simulation_world.add_rigidbody(clay_rb, clay_co);
+
+    // bouncy, but the multiply rule of the clay wins
+    let mut ball_co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    ball_co.material = Material::new(0.6, 0.4, ONE, 1500.0);
+    let mut ball_rb = RigidBody::new(
+        Transform::translation(P3::new(0.95, ZERO, ZERO)),
+        clay_mass,
+        false,
+    );
+    ball_rb.linear_velocity = Vec3::new(-3.0, ZERO, ZERO);
+    let ball_id = simulation_world.add_rigidbody(ball_rb, ball_co);
+
+    simulation_world.collision_world.step();
+    simulation_world.solve_contact_manifolds();
+
+    // perfectly inelastic
+    assert_approx_eq!(
+        simulation_world.rigidbody_ref(clay_id).linear_velocity.x(),
+        ZERO,
+        1e-4
+    );
+    assert_approx_eq!(
+        simulation_world.rigidbody_ref(ball_id).linear_velocity.x(),
+        ZERO,
+        1e-4
+    );
+}
+
+#[test]
+fn penetration_is_corrected_each_step() {
+    for mode in [
+        PositionCorrection::Baumgarte,
+        PositionCorrection::SplitImpulse,
+        PositionCorrection::NonLinearGaussSeidel,
+    ] {
+        let mut simulation_world = SimulationWorld::new(None);
+        simulation_world.solver_parameters.position_correction = mode;
+
+        // Baumgarte turns the correction into velocity, without restitution it doesn't bounce
+        let material = Material::new(0.6, 0.4, 0.0, 1000.0);
+        let mut plane_co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+        plane_co.material = material;
+        let plane_rb = RigidBody::new(Transform::identity(), 0.0, true);
+        simulation_world.add_rigidbody(plane_rb, plane_co);
+
+        // starts 0.3 under the plane
+        let mut obb_co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.5))));
+        obb_co.material = material;
+        let obb_rb = RigidBody::new(Transform::translation(P3::new(0.0, 0.2, 0.0)), 1.0, false);
+        let id = simulation_world.add_rigidbody(obb_rb, obb_co);
+
+        simulation_world.discrete_step();
+        let y = simulation_world.rigidbody_ref(id).transform.translation.y();
+        let max_correction = simulation_world.solver_parameters.max_correction;
+        assert!(y > 0.2, "{:?}", mode);
+        assert!(y < 0.2 + max_correction, "{:?}", mode);
+
+        for _ in 0..240 {
+            simulation_world.discrete_step();
+        }
+        // resting on the plane, the penetration corrected
+        let rb = simulation_world.rigidbody_ref(id);
+        assert!(
+            (rb.transform.translation.y() - 0.5).abs() < 0.02,
+            "{:?} {:?}",
+            mode,
+            rb.transform.translation
+        );
+        assert!(
+            magnitude(&rb.linear_velocity) < 0.05,
+            "{:?} {:?}",
+            mode,
+            rb.linear_velocity
+        );
+    }
+}
+
+#[test]
+fn ball_socket_pendulum_and_chain() {
+    let mut simulation_world = SimulationWorld::new(None);
+    let anchor = P3::new(0.0, 5.0, 0.0);
+
+    let mut ids = Vec::new();
+    for i in 1..3 {
+        let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+        let rb = RigidBody::new(
+            Transform::translation(P3::new(i as Real, 5.0, 0.0)),
+            1.0,
+            false,
+        );
+        ids.push(simulation_world.add_rigidbody(rb, co));
+    }
+    // pinned to the world, then to the first body
+    let joint = BallSocketJoint::new(simulation_world.rigidbody_ref(ids[0]), None, anchor);
+    let handle = simulation_world.add_joint(Box::new(joint));
+    assert_eq!(handle, 0);
+    let joint = BallSocketJoint::new(
+        simulation_world.rigidbody_ref(ids[0]),
+        Some(simulation_world.rigidbody_ref(ids[1])),
+        P3::new(1.0, 5.0, 0.0),
+    );
+    assert_eq!(simulation_world.add_joint(Box::new(joint)), 1);
+
+    let mut lowest = anchor.y();
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        let p1 = simulation_world.rigidbody_ref(ids[0]).transform.translation;
+        let p2 = simulation_world.rigidbody_ref(ids[1]).transform.translation;
+        assert!((magnitude(&(p1 - anchor)) - 1.0).abs() < 0.05);
+        assert!((magnitude(&(p2 - p1)) - 1.0).abs() < 0.05);
+        lowest = helper::min(lowest, p2.y());
+    }
+    // it swings
+    assert!(lowest < 3.5);
+    assert!(simulation_world
+        .joint_ref(handle)
+        .rows()
+        .iter()
+        .any(|row| row.impulse != 0.0));
+}
+
+#[test]
+fn hinge_limits_and_motor() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // lever hinged at its end, falls until a limit
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::new(1.0, 0.1, 0.1))));
+    let rb = RigidBody::new(Transform::translation(P3::new(1.0, 5.0, 0.0)), 1.0, false);
+    let lever_id = simulation_world.add_rigidbody(rb, co);
+    let hinge = HingeJoint::new(
+        simulation_world.rigidbody_ref(lever_id),
+        None,
+        P3::new(0.0, 5.0, 0.0),
+        Directions::forward(),
+    )
+    .with_limits(-0.3, 0.3);
+    let lever = simulation_world.add_joint(Box::new(hinge));
+
+    // wheel driven around the x axis
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let rb = RigidBody::new(Transform::translation(P3::new(5.0, 5.0, 0.0)), 1.0, false);
+    let wheel_id = simulation_world.add_rigidbody(rb, co);
+    let hinge = HingeJoint::new(
+        simulation_world.rigidbody_ref(wheel_id),
+        None,
+        P3::new(5.0, 5.0, 0.0),
+        Directions::right(),
+    )
+    .with_motor(2.0, 100.0);
+    let wheel = simulation_world.add_joint(Box::new(hinge));
+
+    // spun at 0.5 rad per step toward its nearest limit
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let mut rb = RigidBody::new(Transform::translation(P3::new(10.0, 5.0, 0.0)), 1.0, false);
+    rb.angular_velocity = Vec3::new(0.0, 0.0, -30.0);
+    let spinner_id = simulation_world.add_rigidbody(rb, co);
+    let hinge = HingeJoint::new(
+        simulation_world.rigidbody_ref(spinner_id),
+        None,
+        P3::new(10.0, 5.0, 0.0),
+        Directions::forward(),
+    )
+    .with_limits(-0.3, 0.6);
+    let spinner = simulation_world.add_joint(Box::new(hinge));
+
+    let mut max_spinner_angle: Real = 0.0;
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        let hinge = simulation_world
+            .joint_ref(spinner)
+            .downcast_ref::<HingeJoint>()
+            .unwrap();
+        max_spinner_angle = max_spinner_angle.max(hinge.angle().abs());
+    }
+    // the limits are speculative, stopped at the limit instead of crossing it during the step
+    assert!(max_spinner_angle < 0.31);
+
+    let hinge = simulation_world
+        .joint_ref(lever)
+        .downcast_ref::<HingeJoint>()
+        .unwrap();
+    // the lever falls clockwise around the axis, until the lower limit
+    assert!(hinge.angle() > -0.35 && hinge.angle() < -0.25);
+    assert!(hinge.angular_speed().abs() < 0.1);
+    let anchor = simulation_world
+        .rigidbody_ref(lever_id)
+        .transform
+        .transform(&P3::new(-1.0, 0.0, 0.0));
+    assert!(magnitude(&(anchor - P3::new(0.0, 5.0, 0.0))) < 0.02);
+
+    let hinge = simulation_world
+        .joint_ref(wheel)
+        .downcast_ref::<HingeJoint>()
+        .unwrap();
+    assert_approx_eq!(hinge.angular_speed(), 2.0, 1e-2);
+    let w = simulation_world.rigidbody_ref(wheel_id).angular_velocity;
+    assert_approx_eq!(w.x(), 2.0, 1e-2);
+    assert_approx_eq!(w.y(), 0.0, 1e-3);
+    assert_approx_eq!(w.z(), 0.0, 1e-3);
+    let p = simulation_world
+        .rigidbody_ref(wheel_id)
+        .transform
+        .translation;
+    assert!(magnitude(&(p - P3::new(5.0, 5.0, 0.0))) < 0.02);
+}
+
+#[test]
+fn prismatic_limits_and_motor() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // slides along x on the world, against gravity and a spin
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+    let mut rb = RigidBody::new(Transform::translation(P3::new(0.0, 5.0, 0.0)), 1.0, false);
+    rb.angular_velocity = Vec3::new(1.0, 2.0, 3.0);
+    let slider_id = simulation_world.add_rigidbody(rb, co);
+    let joint = PrismaticJoint::new(
+        simulation_world.rigidbody_ref(slider_id),
+        None,
+        P3::new(0.0, 5.0, 0.0),
+        Directions::right(),
+    )
+    .with_limits(-0.5, 0.5)
+    .with_motor(1.0, 100.0);
+    let slider = simulation_world.add_joint(Box::new(joint));
+
+    // a free falling parent carries its child along the parent axis
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+    let rb = RigidBody::new(Transform::translation(P3::new(5.0, 5.0, 0.0)), 1.0, false);
+    let parent_id = simulation_world.add_rigidbody(rb, co);
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+    let rb = RigidBody::new(Transform::translation(P3::new(6.0, 5.0, 0.0)), 1.0, false);
+    let child_id = simulation_world.add_rigidbody(rb, co);
+    let joint = PrismaticJoint::new(
+        simulation_world.rigidbody_ref(parent_id),
+        Some(simulation_world.rigidbody_ref(child_id)),
+        P3::new(6.0, 5.0, 0.0),
+        Directions::right(),
+    )
+    .with_motor(0.0, 100.0);
+    let parented = simulation_world.add_joint(Box::new(joint));
+
+    // thrown at 0.5 per step toward its upper limit, the nearest one
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+    let mut rb = RigidBody::new(Transform::translation(P3::new(10.0, 5.0, 0.0)), 1.0, false);
+    rb.linear_velocity = Vec3::new(30.0, 0.0, 0.0);
+    let thrown_id = simulation_world.add_rigidbody(rb, co);
+    let joint = PrismaticJoint::new(
+        simulation_world.rigidbody_ref(thrown_id),
+        None,
+        P3::new(10.0, 5.0, 0.0),
+        Directions::right(),
+    )
+    .with_limits(-0.6, 0.3);
+    let thrown = simulation_world.add_joint(Box::new(joint));
+
+    let mut max_thrown_translation: Real = 0.0;
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        let joint = simulation_world
+            .joint_ref(thrown)
+            .downcast_ref::<PrismaticJoint>()
+            .unwrap();
+        max_thrown_translation = max_thrown_translation.max(joint.translation());
+    }
+    // the limits are speculative, stopped at the limit instead of crossing it during the step
+    assert!(max_thrown_translation > 0.29 && max_thrown_translation < 0.31);
+    let x = simulation_world
+        .rigidbody_ref(thrown_id)
+        .transform
+        .translation
+        .x();
+    assert_approx_eq!(x, 10.3, 0.02);
+
+    // the motor drives the slider forward until the upper limit
+    let joint = simulation_world
+        .joint_ref(slider)
+        .downcast_ref::<PrismaticJoint>()
+        .unwrap();
+    assert_approx_eq!(joint.translation(), 0.5, 0.02);
+    assert_approx_eq!(joint.speed(), 0.0, 0.05);
+    let rb = simulation_world.rigidbody_ref(slider_id);
+    assert_approx_eq!(rb.transform.translation.x(), 0.5, 0.02);
+    assert_approx_eq!(rb.transform.translation.y(), 5.0, 0.02);
+    assert!(magnitude(&rb.angular_velocity) < 1e-2);
+
+    let joint = simulation_world
+        .joint_ref(parented)
+        .downcast_ref::<PrismaticJoint>()
+        .unwrap();
+    assert_approx_eq!(joint.translation(), 0.0, 0.02);
+    let parent = simulation_world
+        .rigidbody_ref(parent_id)
+        .transform
+        .translation;
+    let child = simulation_world
+        .rigidbody_ref(child_id)
+        .transform
+        .translation;
+    assert!(parent.y() < 0.0);
+    assert_approx_eq!(child.x() - parent.x(), 1.0, 0.02);
+    assert_approx_eq!(child.y() - parent.y(), 0.0, 0.02);
+}
+
+#[test]
+fn fixed_joint_breaks_over_threshold() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // two cantilever beams welded to the world by one end, about 9.81N and 9.81N.m hold them
+    let mut handles = Vec::new();
+    let mut ids = Vec::new();
+    for (z, max_torque) in [(0.0, 20.0), (5.0, 5.0)] {
+        let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::new(1.0, 0.1, 0.1))));
+        let rb = RigidBody::new(Transform::translation(P3::new(1.0, 5.0, z)), 1.0, false);
+        let id = simulation_world.add_rigidbody(rb, co);
+        let joint = FixedJoint::new(
+            simulation_world.rigidbody_ref(id),
+            None,
+            P3::new(0.0, 5.0, z),
+        );
+        let handle = simulation_world.add_joint(Box::new(joint));
+        simulation_world.set_joint_break_threshold(
+            handle,
+            Some(BreakThreshold::new(Real::INFINITY, max_torque)),
+        );
+        handles.push(handle);
+        ids.push(id);
+    }
+
+    let mut broken = Vec::new();
+    for _ in 0..60 {
+        simulation_world.discrete_step();
+        for e in simulation_world.drain_events() {
+            if let SimulationEvent::JointBroken(j) = e {
+                broken.push(j);
+            }
+        }
+    }
+
+    assert_eq!(broken.len(), 1);
+    assert_eq!(broken[0].handle, handles[1]);
+    assert!(broken[0].torque > 5.0);
+    assert!(!simulation_world.is_joint_enabled(handles[1]));
+    assert!(
+        simulation_world
+            .rigidbody_ref(ids[1])
+            .transform
+            .translation
+            .y()
+            < 3.0
+    );
+
+    assert!(simulation_world.is_joint_enabled(handles[0]));
+    let rb = simulation_world.rigidbody_ref(ids[0]);
+    assert!(magnitude(&(rb.transform.translation - P3::new(1.0, 5.0, 0.0))) < 0.02);
+    assert!(magnitude(&rb.angular_velocity) < 0.05);
+    let (linear, angular) = simulation_world.joint_ref(handles[0]).reaction_impulses();
+    let dt = angine::engine::simulation::consts::DELTA_TIME;
+    assert_approx_eq!(magnitude(&linear) / dt, 9.81, 0.5);
+    assert_approx_eq!(magnitude(&angular) / dt, 9.81, 0.5);
+}
+
+#[test]
+fn rope_and_spring() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // slack rope of 2m, the body falls until it is tight
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+    let rb = RigidBody::new(Transform::translation(P3::new(0.0, 4.0, 0.0)), 1.0, false);
+    let rope_id = simulation_world.add_rigidbody(rb, co);
+    let rope = DistanceJoint::new(
+        simulation_world.rigidbody_ref(rope_id),
+        None,
+        P3::new(0.0, 4.0, 0.0),
+        P3::new(0.0, 5.0, 0.0),
+        0.0,
+        2.0,
+    );
+    let rope = simulation_world.add_joint(Box::new(rope));
+
+    // hanging on a spring of rest length 1m
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+    let rb = RigidBody::new(Transform::translation(P3::new(5.0, 4.0, 0.0)), 1.0, false);
+    let spring_id = simulation_world.add_rigidbody(rb, co);
+    let spring = SpringJoint::new(
+        simulation_world.rigidbody_ref(spring_id),
+        None,
+        P3::new(5.0, 4.0, 0.0),
+        P3::new(5.0, 5.0, 0.0),
+        1.0,
+        100.0,
+        5.0,
+    );
+    let spring = simulation_world.add_joint(Box::new(spring));
+
+    let mut was_slack = false;
+    for _ in 0..300 {
+        simulation_world.discrete_step();
+        let rope = simulation_world
+            .joint_ref(rope)
+            .downcast_ref::<DistanceJoint>()
+            .unwrap();
+        assert!(rope.length() < 2.05);
+        was_slack |= rope.length() < 1.9;
+    }
+
+    assert!(was_slack);
+    let rb = simulation_world.rigidbody_ref(rope_id);
+    assert_approx_eq!(rb.transform.translation.y(), 3.0, 0.05);
+    assert!(magnitude(&rb.linear_velocity) < 0.05);
+
+    // k * extension = m * g
+    let spring = simulation_world
+        .joint_ref(spring)
+        .downcast_ref::<SpringJoint>()
+        .unwrap();
+    assert_approx_eq!(spring.length(), 1.0 + 9.81 / 100.0, 0.01);
+    assert!(magnitude(&simulation_world.rigidbody_ref(spring_id).linear_velocity) < 0.01);
+}
+
+#[test]
+fn six_dof_motions_and_drives() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    let mut ids = Vec::new();
+    for x in [0.0, 5.0] {
+        let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+        let rb = RigidBody::new(Transform::translation(P3::new(x, 5.0, 0.0)), 1.0, false);
+        ids.push(simulation_world.add_rigidbody(rb, co));
+    }
+    // attached to the world, the positions are the ones of the body
+    let joint = SixDofJoint::from_world_frame(
+        simulation_world.rigidbody_ref(ids[0]),
+        None,
+        Transform::translation(P3::new(0.0, 5.0, 0.0)),
+    )
+    .with_linear_motion(0, AxisMotion::Limited(JointLimits::new(-0.5, 0.5)))
+    .with_linear_drive(0, AxisDrive::new(1.0, 50.0, 5.0, 100.0))
+    .with_linear_motion(1, AxisMotion::Free);
+    let linear = simulation_world.add_joint(Box::new(joint));
+    // the frame of the body at its center, the one of the world at the same place
+    let joint = SixDofJoint::new(
+        ids[1],
+        None,
+        Transform::identity(),
+        Transform::translation(P3::new(5.0, 5.0, 0.0)),
+    )
+    .with_angular_motion(2, AxisMotion::Free)
+    .with_angular_drive(2, AxisDrive::new(0.5, 5.0, 0.5, 100.0));
+    let angular = simulation_world.add_joint(Box::new(joint));
+
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+    }
+
+    let joint = simulation_world
+        .joint_ref(linear)
+        .downcast_ref::<SixDofJoint>()
+        .unwrap();
+    let rb = simulation_world.rigidbody_ref(ids[0]);
+    // the spring pulls the body to the limit along x
+    assert_approx_eq!(joint.linear_position(0), 0.5, 0.02);
+    assert_approx_eq!(rb.transform.translation.x(), 0.5, 0.02);
+    // falls freely along y, locked along z and around every axis
+    assert!(rb.transform.translation.y() < -10.0);
+    assert_approx_eq!(
+        joint.linear_position(1),
+        rb.transform.translation.y() - 5.0,
+        0.02
+    );
+    assert_approx_eq!(rb.transform.translation.z(), 0.0, 0.02);
+    assert!(magnitude(&rb.angular_velocity) < 0.01);
+
+    let joint = simulation_world
+        .joint_ref(angular)
+        .downcast_ref::<SixDofJoint>()
+        .unwrap();
+    let rb = simulation_world.rigidbody_ref(ids[1]);
+    // turned by the angular drive around z only
+    assert_approx_eq!(joint.angular_position(2), 0.5, 0.02);
+    assert_approx_eq!(joint.angular_position(0), 0.0, 0.02);
+    assert_approx_eq!(joint.angular_position(1), 0.0, 0.02);
+    let x_axis = rb.transform.rotation * Directions::right();
+    assert_approx_eq!(x_axis.y(), (0.5 as Real).sin(), 0.02);
+    assert!(magnitude(&rb.angular_velocity) < 0.05);
+    assert!(magnitude(&(rb.transform.translation - P3::new(5.0, 5.0, 0.0))) < 0.02);
+}
+
+#[test]
+fn cone_twist_swing_and_twist_limits() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // bones along x, held at their end, fall until the cone while spinning around themselves
+    let mut joints = Vec::new();
+    for z in [0.0, 5.0] {
+        let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::new(1.0, 0.1, 0.1))));
+        let mut rb = RigidBody::new(Transform::translation(P3::new(1.0, 5.0, z)), 1.0, false);
+        rb.angular_velocity = Vec3::new(3.0, 0.0, 0.0);
+        let id = simulation_world.add_rigidbody(rb, co);
+        let mut joint = ConeTwistJoint::new(
+            simulation_world.rigidbody_ref(id),
+            None,
+            P3::new(0.0, 5.0, z),
+            Directions::right(),
+        )
+        .with_swing_limit(0.5)
+        .with_twist_limits(-0.4, 0.4);
+        if z > 0.0 {
+            joint = joint.with_swing_softness(50.0, 5.0);
+        }
+        joints.push((id, simulation_world.add_joint(Box::new(joint))));
+    }
+
+    let mut max_twist: Real = ZERO;
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        let joint = simulation_world
+            .joint_ref(joints[0].1)
+            .downcast_ref::<ConeTwistJoint>()
+            .unwrap();
+        assert!(joint.swing_angle() < 0.55);
+        assert!(joint.twist_angle().abs() < 0.45);
+        max_twist = max_twist.max(joint.twist_angle().abs());
+    }
+
+    let (id, handle) = joints[0];
+    let joint = simulation_world
+        .joint_ref(handle)
+        .downcast_ref::<ConeTwistJoint>()
+        .unwrap();
+    // rests against the cone, the twist went to its limit
+    assert_approx_eq!(joint.swing_angle(), 0.5, 0.03);
+    assert!(max_twist > 0.35);
+    let anchor = simulation_world
+        .rigidbody_ref(id)
+        .transform
+        .transform(&P3::new(-1.0, 0.0, 0.0));
+    assert!(magnitude(&(anchor - P3::new(0.0, 5.0, 0.0))) < 0.02);
+
+    // the soft cone gives under the weight
+    let soft_joint = simulation_world
+        .joint_ref(joints[1].1)
+        .downcast_ref::<ConeTwistJoint>()
+        .unwrap();
+    assert!(soft_joint.swing_angle() > joint.swing_angle() + 0.05);
+}
+
+#[test]
+fn ragdoll_from_bones() {
+    let mut simulation_world = SimulationWorld::new(None);
+    let plane_co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+    let plane_rb = RigidBody::new(Transform::identity(), 1.0, true);
+    simulation_world.add_rigidbody(plane_rb, plane_co);
+
+    let shoulder = BoneJoint::ConeTwist {
+        swing_span: 1.0,
+        twist_limits: JointLimits::new(-0.5, 0.5),
+    };
+    let elbow = BoneJoint::Hinge {
+        axis: Directions::forward(),
+        limits: JointLimits::new(-1.5, 0.0),
+    };
+    let bones = [
+        Bone::new(None, Directions::up(), 0.6, 0.1, 3.0, shoulder),
+        Bone::new(Some(0), Directions::up(), 0.25, 0.1, 1.0, shoulder),
+        Bone::new(Some(0), Directions::left(), 0.5, 0.05, 1.0, shoulder),
+        Bone::new(Some(0), Directions::right(), 0.5, 0.05, 1.0, shoulder),
+        Bone::new(Some(2), Directions::left(), 0.5, 0.05, 1.0, elbow),
+    ];
+    let ragdoll = simulation_world.add_ragdoll(P3::new(0.0, 2.0, 0.0), &bones);
+    assert_eq!(ragdoll.rigidbody_ids.len(), bones.len());
+    assert!(ragdoll.joint_handles[0].is_none());
+    assert!(ragdoll.joint_handles[1..]
+        .iter()
+        .all(|handle| handle.is_some()));
+
+    let co_ids: Vec<usize> = ragdoll
+        .rigidbody_ids
+        .iter()
+        .map(|id| simulation_world.rigidbody_ref(*id).collision_object_id())
+        .collect();
+    let collision_world = &simulation_world.collision_world;
+    // parent and child, children of the torso
+    assert!(collision_world.is_pair_ignored(co_ids[0], co_ids[1]));
+    assert!(collision_world.is_pair_ignored(co_ids[2], co_ids[4]));
+    assert!(collision_world.is_pair_ignored(co_ids[3], co_ids[1]));
+    assert!(!collision_world.is_pair_ignored(co_ids[0], co_ids[4]));
+
+    let mut ground_contacts = 0;
+    for _ in 0..240 {
+        simulation_world.discrete_step();
+        for event in simulation_world.events() {
+            if let SimulationEvent::ContactStarted(contact) = event {
+                assert!(!simulation_world
+                    .collision_world
+                    .is_pair_ignored(contact.id_collision_object_a, contact.id_collision_object_b));
+                ground_contacts += 1;
+            }
+        }
+    }
+    assert!(ground_contacts > 0);
+
+    // fallen on the ground, the bones still attached
+    for (i, bone) in bones.iter().enumerate() {
+        let rb = simulation_world.rigidbody_ref(ragdoll.rigidbody_ids[i]);
+        assert!(rb.transform.translation.y() > 0.0 && rb.transform.translation.y() < 0.5);
+        if let Some(parent) = bone.parent {
+            let parent_rb = simulation_world.rigidbody_ref(ragdoll.rigidbody_ids[parent]);
+            let parent_end =
+                parent_rb
+                    .transform
+                    .transform(&P3::new(bones[parent].length / 2.0, 0.0, 0.0));
+            let start = rb
+                .transform
+                .transform(&P3::new(-bone.length / 2.0, 0.0, 0.0));
+            assert!(magnitude(&(parent_end - start)) < 0.05);
+        }
+    }
+}
+
+#[test]
+fn kinematic_platform_carries_a_box() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    // elevator going up and sideways
+    let co = CollisionObject::new(Box::new(shapes::Plane::new(Directions::up())));
+    let mut rb = RigidBody::new(Transform::identity(), 1.0, false);
+    rb.set_kinematic();
+    let platform_id = simulation_world.add_rigidbody(rb, co);
+    simulation_world
+        .rigidbody_mut(platform_id)
+        .set_kinematic_velocities(Vec3::new(1.0, 0.5, 0.0), Vec3::zeros());
+
+    let co = CollisionObject::new(Box::new(shapes::OBB::new(Vec3::value(0.2))));
+    let rb = RigidBody::new(Transform::translation(P3::new(0.0, 0.2, 0.0)), 1.0, false);
+    let box_id = simulation_world.add_rigidbody(rb, co);
+
+    for _ in 0..60 {
+        simulation_world.discrete_step();
+    }
+
+    // the platform is not pushed by the box nor pulled by the gravity
+    let platform = simulation_world.rigidbody_ref(platform_id);
+    assert_approx_eq!(platform.transform.translation.x(), 1.0, 1e-3);
+    assert_approx_eq!(platform.transform.translation.y(), 0.5, 1e-3);
+    assert_approx_eq!(platform.linear_velocity.x(), 1.0, 1e-5);
+    // the box rides on it, carried by the friction
+    let rb = simulation_world.rigidbody_ref(box_id);
+    assert_approx_eq!(rb.linear_velocity.x(), 1.0, 0.02);
+    assert_approx_eq!(rb.linear_velocity.y(), 0.5, 0.02);
+    assert_approx_eq!(rb.transform.translation.y(), 0.7, 0.02);
+
+    // reaches its target in one step then stops
+    let target = Transform::new(Vec3::ones(), Rotation::Y(0.3), Vec3::new(2.0, 0.0, 0.0));
+    simulation_world
+        .rigidbody_mut(platform_id)
+        .set_kinematic_target(target);
+    simulation_world.discrete_step();
+    let platform = simulation_world.rigidbody_ref(platform_id);
+    assert!(magnitude(&(platform.transform.translation - target.translation)) < 1e-3);
+    for i in 0..3 {
+        assert!(magnitude(&(platform.transform.rotation.col(i) - target.rotation.col(i))) < 1e-2);
+    }
+    simulation_world.discrete_step();
+    let platform = simulation_world.rigidbody_ref(platform_id);
+    assert!(magnitude(&platform.linear_velocity) < 1e-3);
+    assert!(magnitude(&platform.angular_velocity) < 1e-3);
+}
+
+#[test]
+fn kinematic_body_crosses_a_static_sensor() {
+    let mut simulation_world = SimulationWorld::new(None);
+
+    let zone_co = CollisionObject::new_sensor(Box::new(shapes::OBB::new(Vec3::value(ONE))));
+    let zone_rb = RigidBody::new(Transform::identity(), 0.0, true);
+    simulation_world.add_rigidbody(zone_rb, zone_co);
+
+    // a door sliding through the zone
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let mut rb = RigidBody::new(Transform::translation(P3::new(-3.0, 0.0, 0.0)), 1.0, false);
+    rb.set_kinematic();
+    let door_id = simulation_world.add_rigidbody(rb, co);
+    simulation_world
+        .rigidbody_mut(door_id)
+        .set_kinematic_velocities(Vec3::new(3.0, 0.0, 0.0), Vec3::zeros());
+
+    let mut events = Vec::new();
+    for _ in 0..120 {
+        simulation_world.discrete_step();
+        events.extend(simulation_world.drain_events());
+    }
+
+    let started = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::OverlapStarted(_)))
+        .unwrap();
+    let stopped = events
+        .iter()
+        .position(|e| matches!(e, SimulationEvent::OverlapStopped(_)))
+        .unwrap();
+    assert!(started < stopped);
+    assert!(
+        simulation_world
+            .rigidbody_ref(door_id)
+            .transform
+            .translation
+            .x()
+            > 2.0
+    );
+}
+
+#[test]
+fn world_gravity_and_gravity_scale() {
+    let mut simulation_world = SimulationWorld::new(None);
+    simulation_world.set_gravity(Vec3::new(5.0, 0.0, 0.0));
+
+    let mut ids = Vec::new();
+    for (z, gravity_scale) in [(0.0, 0.0), (2.0, 1.0), (4.0, 2.0)] {
+        let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+        let mut rb = RigidBody::new(Transform::translation(P3::new(0.0, 0.0, z)), 1.0, false);
+        rb.gravity_scale = gravity_scale;
+        ids.push(simulation_world.add_rigidbody(rb, co));
+    }
+
+    for _ in 0..30 {
+        simulation_world.discrete_step();
+    }
+
+    // sideways, nothing along y
+    let velocities: Vec<Vec3> = ids
+        .iter()
+        .map(|id| simulation_world.rigidbody_ref(*id).linear_velocity)
+        .collect();
+    assert_eq!(velocities[0], Vec3::zeros());
+    assert!(velocities[1].x() > 2.0);
+    assert_approx_eq!(velocities[1].y(), 0.0, 1e-6);
+    assert_approx_eq!(velocities[2].x(), 2.0 * velocities[1].x(), 1e-4);
+
+    // zero-g keeps the velocities
+    simulation_world.set_gravity(Vec3::zeros());
+    simulation_world.discrete_step();
+    let rb = simulation_world.rigidbody_ref(ids[2]);
+    assert_approx_eq!(rb.linear_velocity.x(), velocities[2].x(), 1e-4);
+}
+
+#[test]
+fn linear_and_angular_damping() {
+    let mut simulation_world = SimulationWorld::new(None);
+    simulation_world.set_gravity(Vec3::zeros());
+
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let mut rb = RigidBody::new(Transform::identity(), 1.0, false);
+    rb.linear_damping = 1.0;
+    rb.angular_damping = 2.0;
+    rb.linear_velocity = Vec3::new(3.0, 0.0, 0.0);
+    rb.angular_velocity = Vec3::new(0.0, 4.0, 0.0);
+    let id = simulation_world.add_rigidbody(rb, co);
+
+    // 1 second
+    for _ in 0..60 {
+        simulation_world.discrete_step();
+    }
+
+    let rb = simulation_world.rigidbody_ref(id);
+    assert_approx_eq!(rb.linear_velocity.x(), 3.0 * (-1.0 as Real).exp(), 1e-4);
+    assert_approx_eq!(rb.angular_velocity.y(), 4.0 * (-2.0 as Real).exp(), 1e-4);
+}
+
+#[test]
+fn force_generators() {
+    let mut simulation_world = SimulationWorld::new(None);
+    simulation_world.set_gravity(Vec3::zeros());
+    let add_sphere = |simulation_world: &mut SimulationWorld, position: P3, velocity: Vec3| {
+        let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+        let mut rb = RigidBody::new(Transform::translation(position), 1.0, false);
+        rb.linear_velocity = velocity;
+        simulation_world.add_rigidbody(rb, co)
+    };
+    let spring_id = add_sphere(&mut simulation_world, P3::new(2.0, 0.0, 0.0), Vec3::zeros());
+    let drag_id = add_sphere(
+        &mut simulation_world,
+        P3::new(0.0, 10.0, 0.0),
+        Vec3::new(0.0, 0.0, 10.0),
+    );
+    let wind_id = add_sphere(
+        &mut simulation_world,
+        P3::new(0.0, 20.0, 0.0),
+        Vec3::zeros(),
+    );
+    let sheltered_id = add_sphere(
+        &mut simulation_world,
+        P3::new(0.0, 30.0, 0.0),
+        Vec3::zeros(),
+    );
+    let near_id = add_sphere(
+        &mut simulation_world,
+        P3::new(0.0, -19.0, 0.0),
+        Vec3::zeros(),
+    );
+    let far_id = add_sphere(
+        &mut simulation_world,
+        P3::new(3.0, -20.0, 0.0),
+        Vec3::zeros(),
+    );
+
+    let spring = AnchoredSpring::new(
+        simulation_world.rigidbody_ref(spring_id),
+        P3::new(2.0, 0.0, 0.0),
+        P3::origin(),
+        1.0,
+        10.0,
+        2.0,
+    );
+    simulation_world.add_force_generator(Box::new(spring));
+    simulation_world.add_force_generator(Box::new(QuadraticDrag::new(vec![drag_id], 0.5, 0.0)));
+    let volume = Aabb::from_center_half_extents(P3::new(0.0, 20.0, 0.0), Vec3::new(50.0, 5.0, 5.0));
+    simulation_world.add_force_generator(Box::new(WindVolume::new(
+        volume,
+        Vec3::new(4.0, 0.0, 0.0),
+        1.0,
+    )));
+    let explosion = simulation_world.add_force_generator(Box::new(Explosion::new(
+        P3::new(0.0, -20.0, 0.0),
+        4.0,
+        8.0,
+    )));
+
+    simulation_world.discrete_step();
+    // the impulse falls off with the distance
+    let near = simulation_world.rigidbody_ref(near_id);
+    assert_approx_eq!(near.linear_velocity.y(), 6.0, 1e-3);
+    let far = simulation_world.rigidbody_ref(far_id);
+    assert_approx_eq!(far.linear_velocity.x(), 2.0, 1e-3);
+    assert!(simulation_world
+        .force_generator_ref(explosion)
+        .downcast_ref::<Explosion>()
+        .unwrap()
+        .is_detonated());
+
+    for _ in 0..119 {
+        simulation_world.discrete_step();
+    }
+
+    // only once
+    let near = simulation_world.rigidbody_ref(near_id);
+    assert_approx_eq!(near.linear_velocity.y(), 6.0, 1e-3);
+    // v = v0 / (1 + c * v0 * t) after 2s
+    let rb = simulation_world.rigidbody_ref(drag_id);
+    assert_approx_eq!(rb.linear_velocity.z(), 10.0 / 11.0, 0.05);
+    // v = w - w / (1 + c * w * t), toward the speed of the wind
+    let rb = simulation_world.rigidbody_ref(wind_id);
+    assert_approx_eq!(rb.linear_velocity.x(), 4.0 - 4.0 / 9.0, 0.05);
+    let rb = simulation_world.rigidbody_ref(sheltered_id);
+    assert_eq!(rb.linear_velocity, Vec3::zeros());
+
+    for _ in 0..180 {
+        simulation_world.discrete_step();
+    }
+    // damped toward the rest length
+    let rb = simulation_world.rigidbody_ref(spring_id);
+    assert_approx_eq!(rb.transform.translation.x(), 1.0, 0.02);
+}
+
+#[test]
+fn explosion_with_every_integrator() {
+    for integrator in [
+        Integrator::SemiImplicitEuler,
+        Integrator::VelocityVerlet,
+        Integrator::RungeKutta4,
+    ] {
+        let mut simulation_world = SimulationWorld::new(None);
+        simulation_world.set_gravity(Vec3::zeros());
+        simulation_world.integrator = integrator;
+        let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+        let rb = RigidBody::new(Transform::translation(P3::new(0.0, 1.0, 0.0)), 1.0, false);
+        let id = simulation_world.add_rigidbody(rb, co);
+        let explosion =
+            simulation_world.add_force_generator(Box::new(Explosion::new(P3::origin(), 4.0, 8.0)));
+
+        // the whole impulse, even when the forces are evaluated several times in the step
+        simulation_world.discrete_step();
+        let rb = simulation_world.rigidbody_ref(id);
+        assert_approx_eq!(rb.linear_velocity.y(), 6.0, 0.1);
+        assert!(simulation_world
+            .force_generator_ref(explosion)
+            .downcast_ref::<Explosion>()
+            .unwrap()
+            .is_detonated());
+
+        let velocity = simulation_world.rigidbody_ref(id).linear_velocity.y();
+        simulation_world.discrete_step();
+        let rb = simulation_world.rigidbody_ref(id);
+        assert_eq!(rb.linear_velocity.y(), velocity, "{:?}", integrator);
+    }
+}
+
+#[test]
+fn integrators_energy_drift_on_harmonic_oscillator() {
+    // mass of 1kg on a spring of 100N/m, 10 periods
+    let max_energy_drift = |integrator: Integrator| {
+        let mut simulation_world = SimulationWorld::new(None);
+        simulation_world.set_gravity(Vec3::zeros());
+        simulation_world.integrator = integrator;
+        let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.1)));
+        let rb = RigidBody::new(Transform::translation(P3::new(1.0, 0.0, 0.0)), 1.0, false);
+        let id = simulation_world.add_rigidbody(rb, co);
+        let spring = AnchoredSpring::new(
+            simulation_world.rigidbody_ref(id),
+            P3::new(1.0, 0.0, 0.0),
+            P3::origin(),
+            0.0,
+            100.0,
+            0.0,
+        );
+        simulation_world.add_force_generator(Box::new(spring));
+
+        let energy = |rb: &RigidBody| {
+            0.5 * squared_magnitude(&rb.linear_velocity)
+                + 0.5 * 100.0 * squared_magnitude(&rb.transform.translation)
+        };
+        let initial_energy = energy(simulation_world.rigidbody_ref(id));
+        let mut max_drift: Real = 0.0;
+        for _ in 0..377 {
+            simulation_world.discrete_step();
+            let drift = (energy(simulation_world.rigidbody_ref(id)) - initial_energy).abs();
+            max_drift = max_drift.max(drift / initial_energy);
+        }
+        max_drift
+    };
+
+    let euler = max_energy_drift(Integrator::SemiImplicitEuler);
+    let verlet = max_energy_drift(Integrator::VelocityVerlet);
+    let rk4 = max_energy_drift(Integrator::RungeKutta4);
+    assert!(verlet < euler);
+    assert!(rk4 < verlet);
+    assert!(verlet < 0.01);
+    assert!(rk4 < 0.001);
+}
+
+#[test]
+fn advance_fixed_steps_and_interpolation() {
+    let mut simulation_world = SimulationWorld::new(None);
+    let co = CollisionObject::new(Box::new(shapes::Sphere::new(0.5)));
+    let rb = RigidBody::new(Transform::translation(P3::new(0.0, 10.0, 0.0)), 1.0, false);
+    let id = simulation_world.add_rigidbody(rb, co);
+    let dt = consts::DELTA_TIME;
+
+    // the time left by a call is used by the next one
+    assert_eq!(simulation_world.advance(dt * 0.5), 0);
+    assert_approx_eq!(simulation_world.interpolation_factor(), 0.5, 1e-4);
+    assert_eq!(simulation_world.advance(dt * 2.0), 2);
+    assert_approx_eq!(simulation_world.interpolation_factor(), 0.5, 1e-3);
+
+    // the render transform is between the last two states of the falling body
+    let rb = simulation_world.rigidbody_ref(id);
+    let previous_y = rb.previous_transform().translation.y();
+    let y = rb.transform.translation.y();
+    assert!(y < previous_y);
+    let render_y = simulation_world.render_transform(id).translation.y();
+    assert_approx_eq!(render_y, (previous_y + y) / 2.0, 1e-3);
+
+    // a long frame is capped and the late time dropped
+    simulation_world.max_substeps = 4;
+    assert_eq!(simulation_world.advance(dt * 10.25), 4);
+    let alpha = simulation_world.interpolation_factor();
+    assert!((0.0..1.0).contains(&alpha));
+    assert_eq!(simulation_world.advance(0.0), 0);
+}